@@ -0,0 +1,204 @@
+//! Estimates the costs and the memory footprints of the compiled graphs before training.
+
+use std::collections::BTreeMap;
+
+use crate::error::GraphError;
+use crate::graphs::{Dim, DimKey, Graph, GraphId, Value};
+
+use symengine::{Expression, ExpressionMap, ExpressionMapKey};
+
+/// The costs of a node or a graph, per sample.
+///
+/// The costs are written in terms of the free placeholders of the input node, such as `H`
+/// and `W`, so they become concrete numbers once the placeholders are bound with [`DimKey`].
+///
+/// [`DimKey`]: crate::graphs::DimKey
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cost {
+    /// The number of the trainable parameters.
+    pub params: Expression,
+    /// The number of the multiply-accumulate operations.
+    pub macs: Expression,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphCost {
+    pub total: Cost,
+    /// The costs of each node, including the ones of its child graph.
+    pub nodes: BTreeMap<GraphId, Cost>,
+}
+
+/// The memory footprints of the activations, in bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryEstimate {
+    /// The sizes of the output tensors of each node.
+    pub outputs: BTreeMap<GraphId, Vec<Expression>>,
+    /// The sizes of the tensors which are alive while each node runs.
+    pub live: BTreeMap<GraphId, Expression>,
+}
+
+impl Default for Cost {
+    fn default() -> Self {
+        Self {
+            params: 0u64.into(),
+            macs: 0u64.into(),
+        }
+    }
+}
+
+impl Cost {
+    fn add(self, other: Self) -> Self {
+        Self {
+            params: self.params + other.params,
+            macs: self.macs + other.macs,
+        }
+    }
+}
+
+/// Counts the parameters and the multiply-accumulate operations of the `std/nn` extern models
+/// in the graph, with the inline or non-extern child graphs counted recursively.
+///
+/// Only the std `Conv2d` and `Linear` are counted, and the others are regarded as free,
+/// including the user models of the same names.
+pub fn estimate_cost(graph: &Graph) -> Result<GraphCost, GraphError> {
+    // the placeholders of the child graphs are resolved into the ones of the parents
+    let shapes = graph.get_resolved_shapes(false);
+
+    let mut total = Cost::default();
+    let mut nodes = BTreeMap::new();
+    for (id, node) in graph.get_nodes() {
+        let cost = match (&node.graph, node.inputs.first()) {
+            (Some(child), _) if !child.is_extern() => estimate_cost(child)?.total,
+            // the other models have no parameters, or are not known to have ones
+            (Some(child), Some(input)) if is_std_layer(child) => {
+                let input = &shapes[&input.id][input.arg.unwrap_or(0) as usize];
+                let output = &shapes[id][0];
+                let (in_features, out_features) = (input[0].to_expr(), output[0].to_expr());
+
+                let get_uint = |name: &str| match child
+                    .get_variables()
+                    .get(name)
+                    .and_then(|v| v.value.as_ref())
+                {
+                    Some(Value::UInt(value)) => Ok(*value),
+                    _ => Err(GraphError::NoVariableValue {
+                        name: name.to_string(),
+                    }),
+                };
+
+                let bias = if get_uint("bias")? != 0 {
+                    out_features.clone()
+                } else {
+                    0u64.into()
+                };
+
+                if child.get_std_model() == Some("Conv2d") {
+                    let kernel_size = get_uint("kernel size")?;
+                    let kernel = in_features * kernel_size * kernel_size;
+                    let pixels = output[1].to_expr() * output[2].to_expr();
+                    Cost {
+                        params: kernel.clone() * out_features.clone() + bias,
+                        macs: kernel * out_features * pixels,
+                    }
+                } else {
+                    let weight = in_features * out_features;
+                    Cost {
+                        params: weight.clone() + bias,
+                        macs: weight,
+                    }
+                }
+            }
+            _ => Cost::default(),
+        };
+        total = total.add(cost.clone());
+        nodes.insert(*id, cost);
+    }
+
+    Ok(GraphCost { total, nodes })
+}
+
+/// Tests whether the graph is the std model which has the parameters.
+fn is_std_layer(graph: &Graph) -> bool {
+    matches!(graph.get_std_model(), Some("Conv2d") | Some("Linear"))
+}
+
+/// Computes the sizes of the activations, where each element takes `dtype_size` bytes and
+/// the tensors have `batch` samples, such as a number or [`Dim::batch`] to keep it symbolic.
+///
+/// The nodes run in order, and each tensor is freed right after its last user runs.
+/// The child graphs are regarded as single operations.
+///
+/// [`Dim::batch`]: crate::graphs::Dim::batch
+pub fn estimate_memory(graph: &Graph, dtype_size: u64, batch: Dim) -> MemoryEstimate {
+    let shapes = graph.get_resolved_shapes(true);
+    let mut batches = ExpressionMap::new();
+    batches.insert(DimKey::Batch, batch.to_expr());
+    let nodes = graph.get_nodes();
+
+    let outputs: BTreeMap<_, Vec<_>> = shapes
+        .iter()
+        .map(|(id, shapes)| {
+            let sizes = shapes
+                .iter()
+                .map(|dims| {
+                    let size = dims.iter().fold(Expression::from(dtype_size), |size, dim| {
+                        size * dim.to_expr()
+                    });
+                    batches.eval_once(&size)
+                })
+                .collect();
+            (*id, sizes)
+        })
+        .collect();
+
+    let mut last_uses = BTreeMap::new();
+    for (id, node) in nodes {
+        for input in &node.inputs {
+            last_uses.insert(input.id, *id);
+        }
+    }
+    // the tensors which are not used are the outputs of the graph
+    let last = nodes
+        .keys()
+        .next_back()
+        .copied()
+        .unwrap_or_else(GraphId::new_input);
+
+    let live = nodes
+        .keys()
+        .map(|id| {
+            let size = outputs
+                .iter()
+                .filter(|(output, _)| *output <= id && last_uses.get(output).unwrap_or(&last) >= id)
+                .flat_map(|(_, sizes)| sizes.iter().cloned())
+                .fold(Expression::from(0u64), |total, size| total + size);
+            (*id, size)
+        })
+        .collect();
+
+    MemoryEstimate { outputs, live }
+}
+
+impl MemoryEstimate {
+    /// Finds the node which needs the most memory, with the placeholders bound.
+    ///
+    /// Returns `None` if any placeholder is still free.
+    pub fn peak<K>(&self, symbols: &ExpressionMap<K>) -> Option<(GraphId, Expression)>
+    where
+        K: ExpressionMapKey,
+    {
+        let mut peak: Option<(GraphId, Expression, f64)> = None;
+        for (id, size) in &self.live {
+            let size = symbols.eval_once(size);
+            let value: f64 = size.as_str().parse().ok()?;
+            if peak
+                .as_ref()
+                .map(|(_, _, max)| value > *max)
+                .unwrap_or(true)
+            {
+                peak = Some((*id, size, value));
+            }
+        }
+        peak.map(|(id, size, _)| (id, size))
+    }
+}
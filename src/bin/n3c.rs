@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::io;
+use std::process;
+use std::time::Duration;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use n3_core::watch::{WatchEvent, Watcher};
+use n3_core::*;
+
+const EXIT_OTHER: i32 = 1;
+const EXIT_PARSE: i32 = 2;
+const EXIT_MODEL_NOT_FOUND: i32 = 3;
+const EXIT_SHAPE: i32 = 4;
+const EXIT_MODEL: i32 = 5;
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+const EXIT_CODES: &str = "EXIT CODES:
+    1    I/O and other errors
+    2    Parse errors
+    3    Model not found
+    4    Shape errors
+    5    Malformed models";
+
+fn main() {
+    let matches = App::new("n3c")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Compiles and inspects the n3 models")
+        .after_help(EXIT_CODES)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Checks the model and prints all the errors")
+                .args(&model_args())
+                .arg(
+                    Arg::with_name("watch")
+                        .long("watch")
+                        .help("Checks the model again whenever the model files are changed"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("shapes")
+                .about("Prints the shapes of the nodes")
+                .args(&model_args())
+                .arg(
+                    Arg::with_name("batch")
+                        .long("batch")
+                        .help("Prepends the batch axis to the shapes"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("vars")
+                .about("Prints the resolved variables")
+                .args(&model_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("dump")
+                .about("Dumps the compiled graph")
+                .args(&model_args())
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["json", "bincode", "ir"])
+                        .default_value("json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("graph")
+                .about("Renders the compiled graph")
+                .args(&model_args())
+                .arg(
+                    Arg::with_name("dot")
+                        .long("dot")
+                        .required(true)
+                        .help("Renders in the DOT language of Graphviz"),
+                ),
+        )
+        .get_matches();
+
+    let (command, matches) = matches.subcommand();
+    match run(command, matches.unwrap()) {
+        Ok(()) => {}
+        Err(code) => process::exit(code),
+    }
+}
+
+fn model_args<'a, 'b>() -> [Arg<'a, 'b>; 3] {
+    [
+        Arg::with_name("PATH")
+            .help("The directory of the models")
+            .required(true)
+            .index(1),
+        Arg::with_name("MODEL")
+            .help("The name of the model")
+            .required(true)
+            .index(2),
+        Arg::with_name("shadow")
+            .long("shadow")
+            .value_name("STD MODEL")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Lets the local model replace the std model of the same name"),
+    ]
+}
+
+fn run(command: &str, matches: &ArgMatches) -> Result<(), i32> {
+    let path = matches.value_of("PATH").unwrap();
+    let model = matches.value_of("MODEL").unwrap();
+
+    let shadowing: Vec<_> = matches
+        .values_of("shadow")
+        .map(|models| models.collect())
+        .unwrap_or_default();
+    let mut root = GraphRoot::with_path_shadowing_std(path, &shadowing).map_err(|e| report(&e))?;
+
+    if command == "check" && matches.is_present("watch") {
+        let mut watcher = Watcher::new(path, vec![model.to_string()]);
+        watcher.run(&mut root, WATCH_INTERVAL, |event| {
+            report_event(event);
+            true
+        });
+        return Ok(());
+    }
+
+    if command == "check" {
+        return match root.check_graph(model, UseOrigin::Local) {
+            Ok(graph) => {
+                report_warnings(graph);
+                Ok(())
+            }
+            Err(errors) => {
+                for error in &errors {
+                    report(error);
+                }
+                Err(exit_code(&errors[0]))
+            }
+        };
+    }
+
+    let graph = root
+        .find_graph(model, UseOrigin::Local)
+        .map_err(|e| report(&e))?;
+    report_warnings(graph);
+
+    match command {
+        "shapes" => print_shapes(graph, matches.is_present("batch")),
+        "vars" => print_variables(model, graph),
+        "dump" => dump(model, graph, matches.value_of("format").unwrap())?,
+        "graph" => print!("{}", codegen::dot(model, graph)),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn report(error: &CompileError) -> i32 {
+    eprintln!("{}", error.render());
+    exit_code(error)
+}
+
+fn report_event(event: WatchEvent) {
+    match event {
+        WatchEvent::Changed { path } => eprintln!("changed: {}", path.display()),
+        WatchEvent::Compiled { model } => eprintln!("ok: {}", model),
+        WatchEvent::Failed { errors, .. } => {
+            for error in &errors {
+                report(error);
+            }
+        }
+    }
+}
+
+fn report_warnings(graph: &Graph) {
+    for warning in graph.get_warnings() {
+        eprintln!("warning: {}", warning);
+    }
+}
+
+fn exit_code(error: &CompileError) -> i32 {
+    match error.inner() {
+        CompileError::ParseError { .. } => EXIT_PARSE,
+        CompileError::ModelError {
+            error: ModelError::RecursiveUsage,
+            ..
+        } => EXIT_MODEL,
+        CompileError::ModelError { .. }
+        | CompileError::NonExternModelError {
+            error: NonExternModelError::ModelNotFound,
+            ..
+        } => EXIT_MODEL_NOT_FOUND,
+        CompileError::GraphError { .. } => EXIT_SHAPE,
+        CompileError::DuplicatedModel { .. } => EXIT_MODEL,
+        CompileError::ExternModelError { .. } | CompileError::NonExternModelError { .. } => {
+            EXIT_MODEL
+        }
+        _ => EXIT_OTHER,
+    }
+}
+
+fn print_shapes(graph: &Graph, batch: bool) {
+    let nodes = graph.get_nodes();
+    for (id, shapes) in graph.get_shapes(batch) {
+        let shapes: Vec<_> = shapes
+            .iter()
+            .map(|dims| {
+                let dims: Vec<_> = dims.iter().map(|d| d.to_string()).collect();
+                format!("[{}]", dims.join(", "))
+            })
+            .collect();
+        println!(
+            "{:<10} {:<24} {}",
+            id.to_string(),
+            nodes[&id].name,
+            shapes.join(" ")
+        );
+    }
+}
+
+fn print_variables(model: &str, graph: &Graph) {
+    println!("[{}]", model);
+    print_variables_of(graph.get_variables());
+
+    for (id, node) in graph.get_nodes() {
+        if let Some(graph) = &node.graph {
+            if !graph.get_variables().is_empty() {
+                println!("{} {}", id, node.name);
+                print_variables_of(graph.get_variables());
+            }
+        }
+    }
+}
+
+fn print_variables_of(variables: &HashMap<String, Variable>) {
+    let mut variables: Vec<_> = variables.values().collect();
+    variables.sort_by(|a, b| a.description.cmp(&b.description));
+
+    for variable in variables {
+        let value = match &variable.value {
+            Some(Value::Bool(true)) => "yes".to_string(),
+            Some(Value::Bool(false)) => "no".to_string(),
+            Some(Value::Int(value)) => value.to_string(),
+            Some(Value::UInt(value)) => value.to_string(),
+            Some(Value::Real(value)) => format!("{:?}", value),
+            Some(Value::Model(value)) => value.clone(),
+            None => {
+                println!("    {}: {}", variable.description, variable.ty);
+                continue;
+            }
+        };
+        println!("    {}: {} = {}", variable.description, variable.ty, value);
+    }
+}
+
+fn dump(model: &str, graph: &Graph, format: &str) -> Result<(), i32> {
+    let stdout = io::stdout();
+    let stdout = stdout.lock();
+    let result = match format {
+        "json" => serde_json::to_writer_pretty(stdout, graph).map_err(|e| e.to_string()),
+        "bincode" => bincode::serialize_into(stdout, graph).map_err(|e| e.to_string()),
+        "ir" => {
+            let module = ir::IrModule::new(model, graph);
+            serde_json::to_writer_pretty(stdout, &module).map_err(|e| e.to_string())
+        }
+        _ => unreachable!(),
+    };
+    result.map_err(|error| {
+        eprintln!("error: {}", error);
+        EXIT_OTHER
+    })
+}
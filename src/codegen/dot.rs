@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::{dim_name, input_placeholders};
+use crate::graphs::{Dim, Graph, GraphIdArg};
+
+/// Renders the graph in the DOT language of Graphviz.
+///
+/// Each node becomes a box, and the inline or non-extern child graphs become nested clusters.
+/// The edges are labeled with the inferred shapes.
+pub fn dot(name: &str, graph: &Graph) -> String {
+    let mut code = String::new();
+    writeln!(code, "digraph {} {{", quote(name)).unwrap();
+    writeln!(code, "    compound=true;").unwrap();
+    writeln!(code, "    node [shape=box];").unwrap();
+    write_graph(&mut code, graph, &[], "", 1);
+    writeln!(code, "}}").unwrap();
+    code
+}
+
+/// The ends of a rendered node, which are different if the node is a cluster.
+struct Ends {
+    entry: String,
+    exit: String,
+    cluster: Option<String>,
+}
+
+/// Renders the nodes and the edges of the graph, and returns the ends of the graph.
+fn write_graph(
+    code: &mut String,
+    graph: &Graph,
+    symbols: &[(String, String)],
+    prefix: &str,
+    depth: usize,
+) -> (String, String) {
+    let indent = "    ".repeat(depth);
+    // the placeholders of the child graphs may be given from the parents
+    let symbols = [symbols, &input_placeholders(graph)].concat();
+
+    let mut ends = BTreeMap::new();
+    for (id, node) in graph.get_nodes() {
+        let key = format!("{}{}", prefix, id);
+        let label = format!("{} {}", id, node.name);
+        let (entry, exit, cluster) = match &node.graph {
+            Some(child) if !child.is_extern() => {
+                let cluster = format!("cluster_{}", key);
+                writeln!(code, "{}subgraph {} {{", indent, quote(&cluster)).unwrap();
+                writeln!(code, "{}    label={};", indent, quote(&label)).unwrap();
+                let prefix = format!("{}/", key);
+                let (entry, exit) = write_graph(code, child, &symbols, &prefix, depth + 1);
+                writeln!(code, "{}}}", indent).unwrap();
+                (entry, exit, Some(cluster))
+            }
+            _ => {
+                writeln!(code, "{}{} [label={}];", indent, quote(&key), quote(&label)).unwrap();
+                (key.clone(), key, None)
+            }
+        };
+        ends.insert(
+            *id,
+            Ends {
+                entry,
+                exit,
+                cluster,
+            },
+        );
+    }
+
+    let shapes = graph.get_resolved_shapes(false);
+    for (id, node) in graph.get_nodes() {
+        for input in &node.inputs {
+            let from = &ends[&input.id];
+            let to = &ends[id];
+
+            let label = shape_label(&symbols, input, &shapes[&input.id]);
+            let mut attrs = vec![format!("label={}", quote(&label))];
+            if let Some(cluster) = &from.cluster {
+                attrs.push(format!("ltail={}", quote(cluster)));
+            }
+            if let Some(cluster) = &to.cluster {
+                attrs.push(format!("lhead={}", quote(cluster)));
+            }
+
+            writeln!(
+                code,
+                "{}{} -> {} [{}];",
+                indent,
+                quote(&from.exit),
+                quote(&to.entry),
+                attrs.join(", "),
+            )
+            .unwrap();
+        }
+    }
+
+    let entry = ends.values().next().map(|e| e.entry.clone());
+    let exit = ends.values().next_back().map(|e| e.exit.clone());
+    (entry.unwrap_or_default(), exit.unwrap_or_default())
+}
+
+/// Labels the shapes given through the edge, with the argument indices if needed.
+fn shape_label(symbols: &[(String, String)], input: &GraphIdArg, shapes: &[Vec<Dim>]) -> String {
+    let dims = |dims: &[Dim]| {
+        dims.iter()
+            .map(|dim| dim_name(symbols, input.id, dim))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match input.arg {
+        Some(arg) if shapes.len() > 1 => format!("{}: {}", arg, dims(&shapes[arg as usize])),
+        Some(arg) => dims(&shapes[arg as usize]),
+        None if shapes.len() == 1 => dims(&shapes[0]),
+        None => shapes
+            .iter()
+            .enumerate()
+            .map(|(arg, shape)| format!("{}: {}", arg, dims(shape)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Quotes the text as a DOT string, escaping the quotes, the backslashes and the line breaks.
+///
+/// The other characters are written as they are, since DOT is read in UTF-8.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
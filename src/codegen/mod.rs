@@ -0,0 +1,275 @@
+//! Translates the compiled graphs into the source code of other frameworks.
+
+mod dot;
+mod onnx;
+mod pytorch;
+
+pub use self::dot::dot;
+pub use self::onnx::onnx;
+pub use self::pytorch::pytorch;
+
+use crate::error::CodegenError;
+use crate::graphs::{Dim, DimKey, Graph, GraphId, Node, Value};
+
+use symengine::ExpressionMapKey;
+
+/// An extern model of `std/nn`, with its variables resolved.
+pub(crate) enum Layer {
+    Input,
+    Conv2d {
+        in_channels: Dim,
+        out_channels: Dim,
+        kernel_size: u64,
+        stride: u64,
+        padding: u64,
+        dilation: u64,
+        bias: bool,
+    },
+    Linear {
+        in_features: Dim,
+        out_features: Dim,
+        bias: bool,
+    },
+    ReLU,
+    Softmax {
+        axis: i64,
+    },
+    Dropout {
+        probability: f64,
+    },
+    MaxPool2d {
+        kernel_size: u64,
+        stride: u64,
+        padding: u64,
+        dilation: u64,
+    },
+    Transform,
+}
+
+pub(crate) struct LayerNode {
+    pub id: GraphId,
+    pub layer: Layer,
+    pub input: Option<GraphId>,
+    /// The output shape, without the batch axis.
+    pub shape: Vec<Dim>,
+}
+
+/// A dimension in terms of the free placeholders of the input node.
+pub(crate) enum DimValue {
+    Fixed(u64),
+    Placeholder(String),
+    Expr(String),
+}
+
+/// Resolves the nodes of the graph into the layers, in order.
+pub(crate) fn collect_layers(graph: &Graph) -> Result<Vec<LayerNode>, CodegenError> {
+    let shapes = graph.get_resolved_shapes(false);
+    let single_shape = |id: &GraphId| match shapes[id].as_slice() {
+        [shape] => Ok(shape),
+        _ => Err(CodegenError::UnsupportedArgs { id: *id }),
+    };
+
+    graph
+        .get_nodes()
+        .iter()
+        .map(|(id, node)| {
+            let shape = single_shape(id)?;
+            let input = match node.inputs.as_slice() {
+                [] => None,
+                [input] if input.arg.unwrap_or(0) == 0 => Some(input.id),
+                _ => return Err(CodegenError::UnsupportedArgs { id: *id }),
+            };
+
+            let layer = match input {
+                None if id.is_input() => Layer::Input,
+                None => return Err(unsupported_model(*id, node)),
+                Some(input) => resolve_layer(*id, node, single_shape(&input)?, shape)?,
+            };
+
+            Ok(LayerNode {
+                id: *id,
+                layer,
+                input,
+                shape: shape.clone(),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn resolve_layer(
+    id: GraphId,
+    node: &Node,
+    input: &[Dim],
+    output: &[Dim],
+) -> Result<Layer, CodegenError> {
+    let variables = match &node.graph {
+        Some(graph) if graph.is_extern() => graph.get_variables(),
+        _ => return Err(unsupported_model(id, node)),
+    };
+    let get = |name: &str| {
+        variables
+            .get(name)
+            .and_then(|v| v.value.clone())
+            .ok_or_else(|| CodegenError::NoVariableValue {
+                id,
+                name: name.to_string(),
+            })
+    };
+    let get_uint = |name: &str| match get(name)? {
+        Value::UInt(value) => Ok(value),
+        _ => Err(CodegenError::NoVariableValue {
+            id,
+            name: name.to_string(),
+        }),
+    };
+
+    match node.name.as_str() {
+        "Conv2d" => Ok(Layer::Conv2d {
+            in_channels: input[0].clone(),
+            out_channels: output[0].clone(),
+            kernel_size: get_uint("kernel size")?,
+            stride: get_uint("stride")?,
+            padding: get_uint("padding")?,
+            dilation: get_uint("dilation")?,
+            bias: get_uint("bias")? != 0,
+        }),
+        "Linear" => Ok(Layer::Linear {
+            in_features: input[0].clone(),
+            out_features: output[0].clone(),
+            bias: get_uint("bias")? != 0,
+        }),
+        "ReLU" => Ok(Layer::ReLU),
+        "Softmax" => {
+            let axis = match get("axis")? {
+                Value::Int(axis) => axis,
+                Value::UInt(axis) => axis as i64,
+                _ => {
+                    return Err(CodegenError::NoVariableValue {
+                        id,
+                        name: "axis".to_string(),
+                    })
+                }
+            };
+            // the axis does not count the batch axis
+            let rank = output.len();
+            if axis < -(rank as i64) || axis >= rank as i64 {
+                return Err(CodegenError::AxisOutOfRange { id, axis, rank });
+            }
+            Ok(Layer::Softmax { axis })
+        }
+        "Dropout" => match get("probability")? {
+            Value::Real(probability) => Ok(Layer::Dropout { probability }),
+            Value::UInt(probability) => Ok(Layer::Dropout {
+                probability: probability as f64,
+            }),
+            _ => Err(CodegenError::NoVariableValue {
+                id,
+                name: "probability".to_string(),
+            }),
+        },
+        "MaxPool2d" => Ok(Layer::MaxPool2d {
+            kernel_size: get_uint("kernel size")?,
+            stride: get_uint("stride")?,
+            padding: get_uint("padding")?,
+            dilation: get_uint("dilation")?,
+        }),
+        "Transform" => Ok(Layer::Transform),
+        _ => Err(unsupported_model(id, node)),
+    }
+}
+
+fn unsupported_model(id: GraphId, node: &Node) -> CodegenError {
+    CodegenError::UnsupportedModel {
+        id,
+        model: node.name.clone(),
+    }
+}
+
+/// Returns the symbols and the names of the free placeholders of the input node, in order.
+pub(crate) fn input_placeholders(graph: &Graph) -> Vec<(String, String)> {
+    let shapes = graph.get_shapes(false);
+    shapes
+        .get(&GraphId::new_input())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|dim| match dim {
+            Dim::Key(key @ DimKey::Placeholder(_, true)) => {
+                Some((ExpressionMapKey::to_string(key), key.clone().into_name()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Converts the dimension into an expression of the input placeholders,
+/// with their names prefixed.
+pub(crate) fn resolve_dim(
+    symbols: &[(String, String)],
+    id: GraphId,
+    dim: &Dim,
+    prefix: &str,
+) -> Result<DimValue, CodegenError> {
+    let expr = dim.to_expr();
+    let expr = expr.as_str();
+    if let Ok(value) = expr.parse() {
+        return Ok(DimValue::Fixed(value));
+    }
+
+    let unresolved = || CodegenError::UnresolvedDimension {
+        id,
+        dim: dim.clone(),
+    };
+    let find_symbol = |symbol: &str| {
+        symbols
+            .iter()
+            .find(|(s, _)| s == symbol)
+            .map(|(_, name)| format!("{}{}", prefix, name))
+    };
+    if let Some(name) = find_symbol(expr) {
+        return Ok(DimValue::Placeholder(name));
+    }
+
+    let mut code = String::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            let mut token = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                    token.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            // keep the numbers and the functions, such as `floor`
+            if c.is_ascii_digit() || chars.peek() == Some(&'(') {
+                code.push_str(&token);
+            } else {
+                code.push_str(&find_symbol(&token).ok_or_else(unresolved)?);
+            }
+        } else {
+            code.push(c);
+        }
+    }
+    Ok(DimValue::Expr(code))
+}
+
+/// Writes the dimension in terms of the input placeholders, or as is if not resolved.
+pub(crate) fn dim_name(symbols: &[(String, String)], id: GraphId, dim: &Dim) -> String {
+    match resolve_dim(symbols, id, dim, "") {
+        Ok(DimValue::Fixed(value)) => value.to_string(),
+        Ok(DimValue::Placeholder(name)) | Ok(DimValue::Expr(name)) => name,
+        Err(_) => dim.to_string(),
+    }
+}
+
+/// Names the output of the node, such as `x_1` or `x_1_1_0`.
+pub(crate) fn var_name(prefix: &str, id: GraphId) -> String {
+    if id.pass == 0 && id.repeat == 0 {
+        format!("{}_{}", prefix, id.node)
+    } else {
+        format!("{}_{}_{}_{}", prefix, id.node, id.pass, id.repeat)
+    }
+}
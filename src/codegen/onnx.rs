@@ -0,0 +1,293 @@
+use super::{collect_layers, input_placeholders, resolve_dim, var_name, DimValue, Layer};
+use crate::error::CodegenError;
+use crate::graphs::{Dim, Graph};
+
+const IR_VERSION: i64 = 7;
+const OPSET_VERSION: i64 = 13;
+
+const ELEM_TYPE_FLOAT: i64 = 1;
+const ELEM_TYPE_INT64: i64 = 7;
+
+const ATTRIBUTE_INT: i64 = 2;
+const ATTRIBUTE_INTS: i64 = 7;
+
+/// Exports the graph as a serialized ONNX `ModelProto`.
+///
+/// The free placeholders of the input node, such as `H` and `W`, become the named dynamic
+/// dimensions, and the weights of the layers become the inputs of the graph.
+pub fn onnx(name: &str, graph: &Graph) -> Result<Vec<u8>, CodegenError> {
+    let symbols = input_placeholders(graph);
+    let layers = collect_layers(graph)?;
+
+    let mut proto = Message::default();
+    proto.string(2, name);
+
+    let mut output = None;
+    for node in &layers {
+        let id = node.id;
+        let x = var_name("x", id);
+        let dim = |dim| resolve_dim(&symbols, id, dim, "");
+        let shape = node.shape.iter().map(dim).collect::<Result<Vec<_>, _>>()?;
+
+        let input = match node.input {
+            Some(input) => var_name("x", input),
+            None => {
+                proto.message(11, value_info(&x, &shape));
+                output = Some((x, shape));
+                continue;
+            }
+        };
+        let layer = var_name("layer", id);
+
+        let mut inputs = vec![input];
+        let mut attributes = vec![];
+        let mut add_weight = |suffix: &str, shape: Vec<DimValue>| {
+            let weight = format!("{}.{}", layer, suffix);
+            proto.message(11, tensor_info(&weight, &shape));
+            inputs.push(weight);
+        };
+
+        let op_type = extern_op(&node.layer);
+        match &node.layer {
+            Layer::Input => unreachable!("the input node has no inputs"),
+            Layer::Conv2d {
+                in_channels,
+                out_channels,
+                kernel_size,
+                stride,
+                padding,
+                dilation,
+                bias,
+            } => {
+                let kernel = || DimValue::Fixed(*kernel_size);
+                add_weight(
+                    "weight",
+                    vec![dim(out_channels)?, dim(in_channels)?, kernel(), kernel()],
+                );
+                if *bias {
+                    add_weight("bias", vec![dim(out_channels)?]);
+                }
+
+                attributes.extend(window_attributes(
+                    *kernel_size,
+                    *stride,
+                    *padding,
+                    *dilation,
+                ));
+            }
+            Layer::Linear {
+                in_features,
+                out_features,
+                bias,
+            } => {
+                add_weight("weight", vec![dim(out_features)?, dim(in_features)?]);
+                if *bias {
+                    add_weight("bias", vec![dim(out_features)?]);
+                }
+                attributes.push(attribute_int("transB", 1));
+            }
+            Layer::ReLU => {}
+            Layer::Softmax { axis } => {
+                // skip the batch axis
+                let axis = if *axis < 0 { *axis } else { *axis + 1 };
+                attributes.push(attribute_int("axis", axis));
+            }
+            Layer::Dropout { probability } => {
+                let ratio = format!("{}.ratio", layer);
+                let data = (*probability as f32).to_le_bytes();
+                proto.message(5, tensor(&ratio, ELEM_TYPE_FLOAT, &[], &data));
+                inputs.push(ratio);
+            }
+            Layer::MaxPool2d {
+                kernel_size,
+                stride,
+                padding,
+                dilation,
+            } => {
+                attributes.extend(window_attributes(
+                    *kernel_size,
+                    *stride,
+                    *padding,
+                    *dilation,
+                ));
+            }
+            Layer::Transform => {
+                // keep the batch axis, and infer at most one symbolic dimension
+                let mut target = vec![0];
+                for (value, d) in shape.iter().zip(&node.shape) {
+                    match value {
+                        DimValue::Fixed(value) => target.push(*value as i64),
+                        _ if !target.contains(&-1) => target.push(-1),
+                        _ => return Err(CodegenError::UnresolvedDimension { id, dim: d.clone() }),
+                    }
+                }
+
+                let target_shape = format!("{}.shape", layer);
+                let data: Vec<_> = target
+                    .iter()
+                    .flat_map(|d| d.to_le_bytes().to_vec())
+                    .collect();
+                let dims = [target.len() as i64];
+                proto.message(5, tensor(&target_shape, ELEM_TYPE_INT64, &dims, &data));
+                inputs.push(target_shape);
+            }
+        }
+
+        let mut onnx_node = Message::default();
+        for input in &inputs {
+            onnx_node.string(1, input);
+        }
+        onnx_node.string(2, &x);
+        onnx_node.string(3, &layer);
+        onnx_node.string(4, op_type);
+        for attribute in attributes {
+            onnx_node.message(5, attribute);
+        }
+        proto.message(1, onnx_node);
+
+        output = Some((x, shape));
+    }
+
+    if let Some((x, shape)) = output {
+        proto.message(12, value_info(&x, &shape));
+    }
+
+    let mut opset = Message::default();
+    opset.int(2, OPSET_VERSION);
+
+    let mut model = Message::default();
+    model.int(1, IR_VERSION);
+    model.string(2, "n3-core");
+    model.string(3, env!("CARGO_PKG_VERSION"));
+    model.message(7, proto);
+    model.message(8, opset);
+    Ok(model.0)
+}
+
+/// The mapping table of the extern models in `std/nn` into the ONNX operators.
+fn extern_op(layer: &Layer) -> &'static str {
+    match layer {
+        Layer::Input => "",
+        Layer::Conv2d { .. } => "Conv",
+        Layer::Linear { .. } => "Gemm",
+        Layer::ReLU => "Relu",
+        Layer::Softmax { .. } => "Softmax",
+        Layer::Dropout { .. } => "Dropout",
+        Layer::MaxPool2d { .. } => "MaxPool",
+        Layer::Transform => "Reshape",
+    }
+}
+
+/// Describes a tensor which has the batch axis.
+fn value_info(name: &str, shape: &[DimValue]) -> Message {
+    let batch = DimValue::Placeholder(Dim::batch().to_string());
+    let shape: Vec<_> = Some(&batch).into_iter().chain(shape).collect();
+    value_info_with(name, &shape)
+}
+
+/// Describes a tensor which has no batch axis, such as weights.
+fn tensor_info(name: &str, shape: &[DimValue]) -> Message {
+    let shape: Vec<_> = shape.iter().collect();
+    value_info_with(name, &shape)
+}
+
+fn value_info_with(name: &str, shape: &[&DimValue]) -> Message {
+    let mut tensor_shape = Message::default();
+    for dim in shape {
+        let mut dimension = Message::default();
+        match dim {
+            DimValue::Fixed(value) => dimension.int(1, *value as i64),
+            DimValue::Placeholder(name) | DimValue::Expr(name) => dimension.string(2, name),
+        }
+        tensor_shape.message(1, dimension);
+    }
+
+    let mut tensor_type = Message::default();
+    tensor_type.int(1, ELEM_TYPE_FLOAT);
+    tensor_type.message(2, tensor_shape);
+
+    let mut ty = Message::default();
+    ty.message(1, tensor_type);
+
+    let mut info = Message::default();
+    info.string(1, name);
+    info.message(2, ty);
+    info
+}
+
+fn tensor(name: &str, elem_type: i64, dims: &[i64], data: &[u8]) -> Message {
+    let mut tensor = Message::default();
+    for dim in dims {
+        tensor.int(1, *dim);
+    }
+    tensor.int(2, elem_type);
+    tensor.string(8, name);
+    tensor.bytes(9, data);
+    tensor
+}
+
+/// The attributes of the sliding windows, such as `Conv` and `MaxPool`.
+fn window_attributes(kernel_size: u64, stride: u64, padding: u64, dilation: u64) -> Vec<Message> {
+    let (kernel_size, stride) = (kernel_size as i64, stride as i64);
+    let (padding, dilation) = (padding as i64, dilation as i64);
+    vec![
+        attribute_ints("kernel_shape", &[kernel_size; 2]),
+        attribute_ints("strides", &[stride; 2]),
+        attribute_ints("pads", &[padding; 4]),
+        attribute_ints("dilations", &[dilation; 2]),
+    ]
+}
+
+fn attribute_int(name: &str, value: i64) -> Message {
+    let mut attribute = Message::default();
+    attribute.string(1, name);
+    attribute.int(3, value);
+    attribute.int(20, ATTRIBUTE_INT);
+    attribute
+}
+
+fn attribute_ints(name: &str, values: &[i64]) -> Message {
+    let mut attribute = Message::default();
+    attribute.string(1, name);
+    for value in values {
+        attribute.int(8, *value);
+    }
+    attribute.int(20, ATTRIBUTE_INTS);
+    attribute
+}
+
+/// A protobuf message under encoding.
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    fn int(&mut self, field: u64, value: i64) {
+        self.varint(field << 3 | Self::VARINT);
+        self.varint(value as u64);
+    }
+
+    fn string(&mut self, field: u64, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+
+    fn message(&mut self, field: u64, value: Self) {
+        self.bytes(field, &value.0);
+    }
+
+    fn bytes(&mut self, field: u64, value: &[u8]) {
+        self.varint(field << 3 | Self::LENGTH_DELIMITED);
+        self.varint(value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Write;
+
+use super::{collect_layers, input_placeholders, resolve_dim, var_name, DimValue, Layer};
+use crate::error::CodegenError;
+use crate::graphs::{Dim, Graph, GraphId};
+
+use heck::CamelCase;
+
+/// Emits a self-contained Python module which defines the graph as a PyTorch `nn.Module`.
+///
+/// The free placeholders of the input node, such as `H` and `W`,
+/// become the arguments of the constructor.
+pub fn pytorch(name: &str, graph: &Graph) -> Result<String, CodegenError> {
+    let symbols = input_placeholders(graph);
+    let layers = collect_layers(graph)?;
+
+    let mut init = String::new();
+    let mut forward = String::new();
+    let mut output = GraphId::new_input();
+    for node in &layers {
+        let id = node.id;
+        let x = var_name("x", id);
+        let layer = var_name("self.layer", id);
+        let input = node.input.map(|input| var_name("x", input));
+        let input = input.as_deref().unwrap_or_default();
+        let dim = |dim: &Dim, prefix: &str| to_python(&symbols, id, dim, prefix);
+
+        let module = match &node.layer {
+            Layer::Input => continue,
+            Layer::Conv2d {
+                in_channels,
+                out_channels,
+                kernel_size,
+                stride,
+                padding,
+                dilation,
+                bias,
+            } => format!(
+                "nn.Conv2d({}, {}, kernel_size={}, stride={}, padding={}{}{})",
+                dim(in_channels, "")?,
+                dim(out_channels, "")?,
+                kernel_size,
+                stride,
+                padding,
+                dilation_arg(*dilation),
+                if *bias { "" } else { ", bias=False" },
+            ),
+            Layer::Linear {
+                in_features,
+                out_features,
+                bias,
+            } => format!(
+                "nn.Linear({}, {}, bias={})",
+                dim(in_features, "")?,
+                dim(out_features, "")?,
+                if *bias { "True" } else { "False" },
+            ),
+            Layer::ReLU => "nn.ReLU()".to_string(),
+            Layer::Softmax { axis } => {
+                // skip the batch axis
+                let axis = if *axis < 0 { *axis } else { *axis + 1 };
+                format!("nn.Softmax(dim={})", axis)
+            }
+            Layer::Dropout { probability } => format!("nn.Dropout(p={:?})", probability),
+            Layer::MaxPool2d {
+                kernel_size,
+                stride,
+                padding,
+                dilation,
+            } => format!(
+                "nn.MaxPool2d(kernel_size={}, stride={}, padding={}{})",
+                kernel_size,
+                stride,
+                padding,
+                dilation_arg(*dilation),
+            ),
+            Layer::Transform => {
+                let shape = node
+                    .shape
+                    .iter()
+                    .map(|d| dim(d, "self."))
+                    .collect::<Result<Vec<_>, _>>()?;
+                writeln!(
+                    forward,
+                    "        {} = {}.reshape({}.size(0), {})",
+                    x,
+                    input,
+                    input,
+                    shape.join(", "),
+                )
+                .unwrap();
+                output = id;
+                continue;
+            }
+        };
+
+        writeln!(init, "        {} = {}", layer, module).unwrap();
+        writeln!(forward, "        {} = {}({})", x, layer, input).unwrap();
+        output = id;
+    }
+
+    let mut code = String::new();
+    writeln!(code, "class {}(nn.Module):", name.to_camel_case()).unwrap();
+
+    let args: String = symbols
+        .iter()
+        .map(|(_, name)| format!(", {}", name))
+        .collect();
+    writeln!(code, "    def __init__(self{}):", args).unwrap();
+    writeln!(code, "        super().__init__()").unwrap();
+    if !symbols.is_empty() {
+        for (_, name) in &symbols {
+            writeln!(code, "        self.{} = {}", name, name).unwrap();
+        }
+        writeln!(code).unwrap();
+    }
+    code.push_str(&init);
+    writeln!(code).unwrap();
+
+    writeln!(
+        code,
+        "    def forward(self, {}):",
+        var_name("x", GraphId::new_input())
+    )
+    .unwrap();
+    code.push_str(&forward);
+    writeln!(code, "        return {}", var_name("x", output)).unwrap();
+
+    let mut module = String::new();
+    writeln!(module, "# Generated by n3-core from [{}].", name).unwrap();
+    writeln!(module).unwrap();
+    // the dimensions may be floored in both of the layers and the reshapes
+    if code.contains("math.") {
+        writeln!(module, "import math").unwrap();
+        writeln!(module).unwrap();
+    }
+    writeln!(module, "from torch import nn").unwrap();
+    writeln!(module).unwrap();
+    writeln!(module).unwrap();
+    module.push_str(&code);
+    Ok(module)
+}
+
+/// Omits the default dilation, which is rarely used.
+fn dilation_arg(dilation: u64) -> String {
+    if dilation == 1 {
+        String::new()
+    } else {
+        format!(", dilation={}", dilation)
+    }
+}
+
+/// Converts the dimension into a Python expression of the input placeholders.
+fn to_python(
+    symbols: &[(String, String)],
+    id: GraphId,
+    dim: &Dim,
+    prefix: &str,
+) -> Result<String, CodegenError> {
+    match resolve_dim(symbols, id, dim, prefix)? {
+        DimValue::Fixed(value) => Ok(value.to_string()),
+        DimValue::Placeholder(name) => Ok(name),
+        DimValue::Expr(expr) => {
+            let expr = expr.replace("floor(", "math.floor(");
+            Ok(format!("int({})", expr))
+        }
+    }
+}
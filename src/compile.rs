@@ -1,5 +1,6 @@
 use crate::error::{CompileError, ExternModelError, GraphError, NonExternModelError};
 use crate::graphs::*;
+use crate::warning::CompileWarning;
 
 use n3_parser::ast;
 
@@ -9,14 +10,42 @@ impl<'a> Compile<'a> for ast::File {
 
     fn compile(self, root: Self::Args) -> Result<Self::Output, CompileError> {
         let mut graph = Graph::new(self.model.is_extern);
+        if root.is_recovering() {
+            graph.start_recovery();
+        }
 
+        let mut uses = vec![];
         for model in self.uses {
-            let (name, use_g) = model.compile(root)?;
-            graph.add_graph(name, use_g.clone());
+            let name = model.model.clone();
+            match model.compile(root) {
+                Ok((name, use_g)) => {
+                    uses.push(name.clone());
+                    graph.add_graph(name, use_g.clone());
+                }
+                Err(error) => {
+                    graph.recover(error)?;
+                    graph.add_graph(name, Graph::new_poisoned());
+                }
+            }
         }
 
-        let (_, graph) = self.model.compile(&mut graph)?;
-        Ok(graph)
+        let name = self.model.name.clone();
+        let (_, mut model) = self
+            .model
+            .compile(&mut graph)
+            .map_err(|error| error.in_model(&name))?;
+        // keep the errors of the uses first
+        graph.merge_errors(&mut model);
+        model.merge_errors(&mut graph);
+
+        if !model.is_poisoned() {
+            for name in uses {
+                if !model.is_graph_used(&name) {
+                    model.warn(CompileWarning::UnusedUse { model: name });
+                }
+            }
+        }
+        Ok(model)
     }
 }
 
@@ -25,7 +54,7 @@ impl<'a> Compile<'a> for ast::Use {
     type Output = (String, &'a Graph);
 
     fn compile(self, root: Self::Args) -> Result<Self::Output, CompileError> {
-        let model = root.find_graph(&self.model, self.origin)?;
+        let model = root.find_use(&self.model, self.origin)?;
         Ok((self.model, model))
     }
 }
@@ -50,7 +79,12 @@ impl<'a> Compile<'a> for ast::Model {
                 });
             }
 
-            let prefab = Graph::new(true);
+            let mut prefab = Graph::new(true);
+            if parent.has_graph(&self.name) {
+                prefab.warn(CompileWarning::ShadowedModel {
+                    model: self.name.clone(),
+                });
+            }
 
             (prefab, false)
         } else {
@@ -82,15 +116,20 @@ impl<'a> Compile<'a> for ast::Model {
 
                     let mut prefab = parent.new_child();
 
-                    let children = self
-                        .inner
-                        .children
-                        .into_iter()
-                        .map(|child| {
-                            let (name, child) = child.compile(&mut prefab)?;
-                            Ok((name, child))
-                        })
-                        .collect::<Result<Vec<_>, CompileError>>()?;
+                    let mut children = vec![];
+                    for child in self.inner.children {
+                        let name = child.name.clone();
+                        match child.compile(&mut prefab) {
+                            Ok((name, mut child)) => {
+                                prefab.merge_errors(&mut child);
+                                children.push((name, child));
+                            }
+                            Err(error) => {
+                                prefab.recover(error.in_model(&name))?;
+                                children.push((name, Graph::new_poisoned()));
+                            }
+                        }
+                    }
 
                     // once the children have been compiled all,
                     // add them respectively
@@ -103,46 +142,64 @@ impl<'a> Compile<'a> for ast::Model {
             }
         };
 
-        if is_override {
+        let mut declared = vec![];
+        if is_override && child.is_poisoned() {
+            // the variables of broken graphs are unknown
+        } else if is_override {
             for variable in self.inner.variables {
                 let (name, variable) = variable.compile(())?;
                 let description = variable.description;
                 let ty = variable.ty;
-                if let Some(variable) = variable.value {
-                    if let Err(error) =
-                        child.update_variable(Some(description), Some(name), variable, ty)
+                let result = if let Some(variable) = variable.value {
+                    child.use_value(&variable);
+                    if let Some(warning) =
+                        child.check_redundant_variable(&self.name, &description, &variable)
                     {
-                        return Err(CompileError::GraphError {
-                            error,
-                            model: self.name,
-                        });
+                        child.warn(warning);
                     }
+                    child.update_variable(Some(description), Some(name), variable, ty)
                 } else {
-                    return Err(CompileError::GraphError {
-                        error: GraphError::NoVariableValue { name },
-                        model: self.name,
-                    });
+                    Err(GraphError::NoVariableValue { name })
+                };
+                if let Err(error) = result {
+                    child.recover(CompileError::GraphError {
+                        error,
+                        model: self.name.clone(),
+                    })?;
                 }
             }
         } else {
             for variable in self.inner.variables {
                 let (name, variable) = variable.compile(())?;
+                if let Some(value) = &variable.value {
+                    child.use_value(value);
+                }
+                declared.push(variable.description.clone());
                 if let Err(error) = child.add_variable(Some(name), variable) {
-                    return Err(CompileError::GraphError {
+                    child.recover(CompileError::GraphError {
                         error,
-                        model: self.name,
-                    });
+                        model: self.name.clone(),
+                    })?;
                 }
             }
         }
 
         for node in self.inner.graph {
-            node.compile(&mut child)?;
+            if let Err(error) = node.compile(&mut child) {
+                child.recover(error)?;
+            }
         }
 
         if !self.is_extern && !is_override {
-            child.finalize()?;
+            if let Err(error) = child.finalize() {
+                child.recover(error)?;
+            }
+            if !child.is_poisoned() {
+                child.check_unused_variables(&self.name, &declared);
+            }
         }
+        // the errors are located in the block of the model, as the others may reuse its node ids
+        child.locate_errors(&self.name);
         Ok((self.name, child))
     }
 }
@@ -158,10 +215,11 @@ impl<'a> Compile<'a> for ast::Variable {
             self.description.clone()
         };
 
+        let value = self.default.map(restore_sign);
         let variable = Variable {
             description: self.description,
-            ty: ValueType::new(self.default.as_ref(), self.is_model),
-            value: self.default,
+            ty: ValueType::new(value.as_ref(), self.is_model),
+            value,
         };
 
         Ok((name, variable))
@@ -174,8 +232,17 @@ impl<'a> Compile<'a> for ast::Graph {
 
     fn compile(self, graph: Self::Args) -> Result<Self::Output, CompileError> {
         let mut inline = if let Some(inline) = self.inline {
-            let (_, inline) = inline.compile(graph)?;
-            Some(inline)
+            let name = inline.name.clone();
+            match inline.compile(graph) {
+                Ok((_, mut inline)) => {
+                    graph.merge_errors(&mut inline);
+                    Some(inline)
+                }
+                Err(error) => {
+                    graph.recover(error.in_model(&name))?;
+                    Some(Graph::new_poisoned())
+                }
+            }
         } else {
             None
         };
@@ -188,18 +255,45 @@ impl<'a> Compile<'a> for ast::Graph {
                     repeat,
                 };
 
-                graph.attach(id, pass.name.clone(), inline.take(), pass.args.clone())?;
+                if let Err(error) =
+                    graph.attach(id, pass.name.clone(), inline.take(), pass.args.clone())
+                {
+                    graph.recover(error)?;
+                    graph.attach_poisoned(id, pass.name.clone());
+                }
             }
         }
 
         if let Some(shapes) = self.shapes {
-            graph.adjust_shapes(shapes)
-        } else {
-            Ok(())
+            // keep the declared shapes to continue with them on failure
+            let declared = if graph.is_recovering() {
+                Some(clone_shapes(&shapes))
+            } else {
+                None
+            };
+
+            if let Err(error) = graph.adjust_shapes(shapes) {
+                graph.recover(error)?;
+                graph.poison_last_node();
+                if let Some(declared) = declared {
+                    graph.adjust_shapes(declared).ok();
+                }
+            }
         }
+        Ok(())
     }
 }
 
+fn clone_shapes(shapes: &ast::Shapes) -> ast::Shapes {
+    ast::Shapes(
+        shapes
+            .0
+            .iter()
+            .map(|(&arg, shape)| (arg, ast::Shape(shape.0.clone())))
+            .collect(),
+    )
+}
+
 pub trait Compile<'a> {
     type Args;
     type Output;
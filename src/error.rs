@@ -1,4 +1,7 @@
-use crate::graphs::{Dim, GraphId, Value, ValueType};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::graphs::{DType, Dim, GraphId, Value, ValueType};
 
 use n3_parser::ast;
 
@@ -28,6 +31,38 @@ pub enum CompileError {
         error: n3_parser::error::ParseError,
         path: std::path::PathBuf,
     },
+    /// Two files declare the models of the same name.
+    DuplicatedModel {
+        model: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+    /// An error located in a source file.
+    SpannedError {
+        error: Box<CompileError>,
+        span: Span,
+    },
+    /// An error which has occurred in the graph of the model, not located yet.
+    ///
+    /// It is located only in the block of the model, as the other models may reuse its node ids.
+    InModel {
+        error: Box<CompileError>,
+        model: String,
+    },
+}
+
+/// A location of a line in a `.n3` source file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub path: PathBuf,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number.
+    pub column: usize,
+    /// The number of characters to underline.
+    pub len: usize,
+    /// The whole source line.
+    pub snippet: String,
 }
 
 #[derive(Debug)]
@@ -48,6 +83,8 @@ pub enum NonExternModelError {
 #[derive(Debug)]
 pub enum ModelError {
     ModelNotFound,
+    SiteNotFound,
+    UserNotFound,
     RecursiveUsage,
 }
 
@@ -112,6 +149,47 @@ pub enum GraphError {
         id: GraphId,
         arg: u64,
     },
+    DifferentBinding {
+        name: String,
+        expected: Dim,
+        given: u64,
+    },
+    NonIntegerDimension {
+        id: GraphId,
+        arg: u64,
+        axis: usize,
+        dim: Dim,
+    },
+    DifferentDType {
+        id: GraphId,
+        arg: u64,
+        expected: DType,
+        given: DType,
+    },
+    ReservedVariable {
+        name: String,
+    },
+    AxisOutOfRange {
+        id: GraphId,
+        axis: i64,
+        rank: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum CodegenError {
+    UnsupportedModel { id: GraphId, model: String },
+    UnsupportedArgs { id: GraphId },
+    NoVariableValue { id: GraphId, name: String },
+    UnresolvedDimension { id: GraphId, dim: Dim },
+    AxisOutOfRange { id: GraphId, axis: i64, rank: usize },
+}
+
+#[cfg(feature = "ir")]
+#[derive(Debug)]
+pub enum IrError {
+    JsonError { error: serde_json::Error },
+    UnsupportedVersion { version: Option<u64> },
 }
 
 impl From<std::io::Error> for CompileError {
@@ -119,3 +197,552 @@ impl From<std::io::Error> for CompileError {
         Self::OsError { error }
     }
 }
+
+impl CompileError {
+    /// Returns the error without its source location.
+    pub fn inner(&self) -> &Self {
+        match self {
+            Self::SpannedError { error, .. } | Self::InModel { error, .. } => error.inner(),
+            _ => self,
+        }
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Self::SpannedError { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+
+    /// Renders the error with a caret-underlined snippet of the source, if located.
+    pub fn render(&self) -> String {
+        match self {
+            Self::SpannedError { error, span } => match &**error {
+                Self::ParseError { error, .. } => span.render(error),
+                error => span.render(error),
+            },
+            _ => format!("error: {}", self),
+        }
+    }
+
+    /// Marks the error as occurred in the graph of the model, if not yet located.
+    pub(crate) fn in_model(self, model: &str) -> Self {
+        match self {
+            Self::SpannedError { .. } | Self::InModel { .. } => self,
+            error => Self::InModel {
+                error: Box::new(error),
+                model: model.to_string(),
+            },
+        }
+    }
+
+    /// Locates the error in the given source, if not yet located.
+    ///
+    /// The spans are found by searching the source for the models, the nodes and the variables
+    /// of the error, as the parser gives no positions. So they are best-effort: the search is
+    /// limited to the block of the model which the error has occurred in, but the models of
+    /// the same name, such as the overrides of `[Conv2d]` in different children, may be confused.
+    pub(crate) fn with_source(self, path: &Path, source: &str) -> Self {
+        let (error, line) = match self {
+            Self::SpannedError { .. } => return self,
+            Self::InModel { error, model } => {
+                let line = model_block(source, &model)
+                    .and_then(|block| error.find_line(&block))
+                    .or_else(|| error.find_line(source));
+                (*error, line)
+            }
+            error => {
+                let line = error.find_line(source);
+                (error, line)
+            }
+        };
+
+        match line {
+            Some((line, column)) => {
+                let snippet = source.lines().nth(line - 1).unwrap_or_default();
+                let len = match &error {
+                    Self::ParseError { .. } => 1,
+                    _ => (snippet.trim_end().chars().count() + 1).saturating_sub(column),
+                };
+                Self::SpannedError {
+                    error: Box::new(error),
+                    span: Span {
+                        path: path.to_path_buf(),
+                        line,
+                        column,
+                        len: len.max(1),
+                        snippet: snippet.to_string(),
+                    },
+                }
+            }
+            None => error,
+        }
+    }
+
+    fn find_line(&self, source: &str) -> Option<(usize, usize)> {
+        match self {
+            Self::ExternModelError { error, model } => match error {
+                ExternModelError::UnexpectedChild { model } => find_model(source, model),
+                _ => None,
+            }
+            .or_else(|| find_model(source, model)),
+            Self::NonExternModelError { error, model } => match error {
+                NonExternModelError::ModelNotFound => find_node_using(source, model),
+                _ => None,
+            }
+            .or_else(|| find_model(source, model)),
+            Self::ModelError { model, .. } => find_use(source, model),
+            Self::GraphError { error, model } => match error.variable() {
+                Some(variable) => find_variable(source, variable),
+                None => error
+                    .id()
+                    .and_then(|id| find_node(source, id.node, model))
+                    .or_else(|| find_node_using(source, model)),
+            },
+            Self::OsError { .. } | Self::DuplicatedModel { .. } => None,
+            Self::ParseError { error, .. } => {
+                let (line, column) = (error.location.row(), error.location.column());
+                if line > 0 {
+                    Some((line, column.max(1)))
+                } else {
+                    None
+                }
+            }
+            Self::SpannedError { span, .. } => Some((span.line, span.column)),
+            Self::InModel { error, .. } => error.find_line(source),
+        }
+    }
+}
+
+impl GraphError {
+    /// Returns the node the error has occurred at, if any.
+    pub fn id(&self) -> Option<&GraphId> {
+        match self {
+            Self::UnvalidNodeId { id, .. }
+            | Self::UnvalidNodeArg { id, .. }
+            | Self::ShapeNotDefined { id }
+            | Self::FullShapeRequired { id }
+            | Self::CannotEstimateShape { id, .. }
+            | Self::DifferentDimension { id, .. }
+            | Self::DifferentArgs { id, .. }
+            | Self::DifferentRank { id, .. }
+            | Self::DivideByZero { id, .. }
+            | Self::NonIntegerDimension { id, .. }
+            | Self::DifferentDType { id, .. }
+            | Self::AxisOutOfRange { id, .. } => Some(id),
+            Self::NoSuchNode { query_id, .. } => Some(query_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the variable the error has occurred at, if any.
+    pub fn variable(&self) -> Option<&str> {
+        match self {
+            Self::NoSuchVariable { name }
+            | Self::NoVariableValue { name }
+            | Self::ReservedVariable { name }
+            | Self::DifferentBinding { name, .. } => Some(name),
+            Self::DifferentVariableType { variable, .. } => Some(variable),
+            _ => None,
+        }
+    }
+}
+
+impl Span {
+    pub fn render(&self, message: &dyn fmt::Display) -> String {
+        let line = self.line.to_string();
+        let margin = " ".repeat(line.len());
+
+        format!(
+            "error: {message}\n\
+             {margin}--> {span}\n\
+             {margin} |\n\
+             {line} | {snippet}\n\
+             {margin} | {pad}{carets}",
+            message = message,
+            margin = margin,
+            span = self,
+            line = line,
+            snippet = self.snippet.trim_end(),
+            pad = " ".repeat(self.column - 1),
+            carets = "^".repeat(self.len),
+        )
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.as_os_str().is_empty() {
+            write!(f, "<source>")?;
+        } else {
+            write!(f, "{}", self.path.display())?;
+        }
+        write!(f, ":{}:{}", self.line, self.column)
+    }
+}
+
+fn find_line<F>(source: &str, f: F) -> Option<(usize, usize)>
+where
+    F: Fn(&str) -> bool,
+{
+    source.lines().enumerate().find_map(|(row, line)| {
+        let trimmed = line.trim_start();
+        if f(trimmed) {
+            // the columns count the characters, as the carets do
+            Some((row + 1, line.chars().count() - trimmed.chars().count() + 1))
+        } else {
+            None
+        }
+    })
+}
+
+/// Blanks the lines out of the block of the model, keeping the line numbers.
+///
+/// The block ends before the next line which is indented not more than its header.
+/// The blocks of its children are blanked too, but not their headers, which are declared in it.
+fn model_block(source: &str, model: &str) -> Option<String> {
+    let (header, column) = find_model(source, model)?;
+    let indent = |line: &str| line.chars().count() - line.trim_start().chars().count();
+    let is_child = |line: &str| {
+        let line = line.trim_start();
+        let line = match line.strip_prefix('#') {
+            Some(node) => node.trim_start_matches(|c: char| c.is_ascii_digit()),
+            None => line.trim_start_matches("extern"),
+        };
+        line.trim_start().starts_with('[')
+    };
+
+    let mut block = String::new();
+    let mut child = None;
+    for (row, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        let is_kept = if row + 1 == header {
+            true
+        } else if row + 1 < header || trimmed.is_empty() || trimmed.starts_with("//") {
+            false
+        } else if indent(line) < column {
+            break;
+        } else if child.map(|child| indent(line) > child).unwrap_or(false) {
+            false
+        } else {
+            child = if is_child(line) {
+                Some(indent(line))
+            } else {
+                None
+            };
+            true
+        };
+
+        if is_kept {
+            block.push_str(line);
+        }
+        block.push('\n');
+    }
+    Some(block)
+}
+
+fn find_model(source: &str, model: &str) -> Option<(usize, usize)> {
+    let header = format!("[{}]", model);
+    find_line(source, |l| {
+        l.trim_start_matches("extern").trim_start() == header
+    })
+    .or_else(|| find_line(source, |l| l.starts_with('#') && l.ends_with(&header)))
+}
+
+fn find_node(source: &str, node: u64, model: &str) -> Option<(usize, usize)> {
+    find_line(source, |l| is_node(l, node) && l.contains(model))
+        .or_else(|| find_line(source, |l| is_node(l, node)))
+}
+
+fn is_node(line: &str, node: u64) -> bool {
+    match line.strip_prefix('#') {
+        Some(id) => {
+            let id: String = id.chars().take_while(char::is_ascii_digit).collect();
+            id.parse() == Ok(node)
+        }
+        None => false,
+    }
+}
+
+fn find_node_using(source: &str, model: &str) -> Option<(usize, usize)> {
+    find_line(source, |l| l.starts_with('#') && l.contains(model))
+}
+
+fn find_variable(source: &str, variable: &str) -> Option<(usize, usize)> {
+    find_line(source, |l| is_variable(l, variable))
+        .or_else(|| find_line(source, |l| l.starts_with('#') && contains_word(l, variable)))
+}
+
+/// Tests whether the line declares the variable, such as `* S: stride = 1`.
+fn is_variable(line: &str, variable: &str) -> bool {
+    match line.strip_prefix('*') {
+        Some(decl) => {
+            let decl = decl.split('=').next().unwrap_or_default();
+            let decl = decl.trim().trim_start_matches('[').trim_end_matches(']');
+            decl.split(':').any(|part| part.trim() == variable)
+        }
+        None => false,
+    }
+}
+
+/// Tests whether the line has the word, not as a part of the other words.
+fn contains_word(line: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    line.match_indices(word).any(|(start, _)| {
+        let end = start + word.len();
+        !line[..start].ends_with(is_ident) && !line[end..].starts_with(is_ident)
+    })
+}
+
+fn find_use(source: &str, model: &str) -> Option<(usize, usize)> {
+    // the name should be followed by the origin or nothing, not as a part of the other names
+    let is_use = |l: &str| match l.strip_prefix("use ") {
+        Some(l) => match l.trim_start().strip_prefix(model) {
+            Some(rest) => rest.is_empty() || rest.starts_with(char::is_whitespace),
+            None => false,
+        },
+        None => false,
+    };
+    find_line(source, is_use).or_else(|| find_node_using(source, model))
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ExternModelError { error, model } => {
+                write!(f, "{} (in extern model [{}])", error, model)
+            }
+            Self::NonExternModelError { error, model } => {
+                write!(f, "{} (in model [{}])", error, model)
+            }
+            Self::ModelError {
+                error,
+                model,
+                origin,
+            } => match origin {
+                ast::UseOrigin::Site(site) => write!(f, "{}: {} by {:?}", error, model, site),
+                ast::UseOrigin::User(user) => write!(f, "{}: {} by {}", error, model, user),
+                ast::UseOrigin::Local => write!(f, "{}: {}", error, model),
+            },
+            Self::GraphError { error, model } => write!(f, "{} (in [{}])", error, model),
+            Self::OsError { error } => write!(f, "{}", error),
+            Self::ParseError { error, path } => {
+                if path.as_os_str().is_empty() {
+                    write!(f, "{}", error)
+                } else {
+                    write!(f, "{}: {}", path.display(), error)
+                }
+            }
+            Self::DuplicatedModel {
+                model,
+                first,
+                second,
+            } => write!(
+                f,
+                "duplicated model [{}]: {} and {}",
+                model,
+                first.display(),
+                second.display()
+            ),
+            Self::SpannedError { error, span } => write!(f, "{}: {}", span, error),
+            Self::InModel { error, .. } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::OsError { error } => Some(error),
+            Self::ParseError { error, .. } => Some(error),
+            Self::SpannedError { error, .. } => Some(&**error),
+            Self::InModel { error, .. } => std::error::Error::source(&**error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ExternModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownGraph => write!(f, "extern models should have exactly 2 nodes"),
+            Self::MalformedShape => write!(f, "malformed shape"),
+            Self::UnexpectedChild { model } => {
+                write!(f, "extern models cannot have a child model [{}]", model)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NonExternModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoGraph => write!(f, "the model has no graph"),
+            Self::ModelNotFound => write!(f, "no such model"),
+            Self::OverrideChild => write!(f, "cannot add children while overriding a model"),
+            Self::OverrideGraph => write!(f, "cannot add a graph while overriding a model"),
+        }
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ModelNotFound => write!(f, "model not found"),
+            Self::SiteNotFound => write!(f, "site not found"),
+            Self::UserNotFound => write!(f, "user not found"),
+            Self::RecursiveUsage => write!(f, "recursive model usage"),
+        }
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InputNodeNotFound => write!(f, "input node (#0) not found"),
+            Self::FirstNodeNotFound => write!(f, "the first node should be the input node (#0)"),
+            Self::UnvalidNodeId { last, id } => {
+                write!(f, "invalid node id {}: the last node is {}", id, last)
+            }
+            Self::UnvalidNodeArg { id, arg, given } => write!(
+                f,
+                "invalid argument index at {}: expected {}, given {}",
+                id, arg, given
+            ),
+            Self::ShapeNotDefined { id } => write!(f, "the shape of {} is not defined", id),
+            Self::FullShapeRequired { id } => write!(f, "{} requires the full shape", id),
+            Self::NoSuchVariable { name } => write!(f, "no such variable: {}", name),
+            Self::NoVariableValue { name } => write!(f, "variable {} has no value", name),
+            Self::NoSuchNode { query_id, node } => {
+                write!(f, "no such node #{} (queried at {})", node, query_id)
+            }
+            Self::CannotEstimateShape { id, arg, axis } => write!(
+                f,
+                "cannot estimate the shape of {}:{} at axis {}",
+                id, arg, axis
+            ),
+            Self::DifferentDimension {
+                id,
+                arg,
+                axis,
+                expected,
+                given,
+            } => write!(
+                f,
+                "different dimension at {}:{} axis {}: expected {}, given {}",
+                id, arg, axis, expected, given
+            ),
+            Self::DifferentArgs {
+                id,
+                last_args,
+                args,
+            } => write!(
+                f,
+                "different arguments at {}: expected {:?}, given {:?}",
+                id, last_args, args
+            ),
+            Self::DifferentRank {
+                id,
+                arg,
+                last_rank,
+                rank,
+            } => write!(
+                f,
+                "different rank at {}:{}: expected {}, given {}",
+                id, arg, last_rank, rank
+            ),
+            Self::DifferentVariableType {
+                variable,
+                expected,
+                given,
+            } => match given {
+                Some(given) => write!(
+                    f,
+                    "variable {} should be {}, given {:?}",
+                    variable, expected, given
+                ),
+                None => write!(f, "variable {} should be {}", variable, expected),
+            },
+            Self::DivideByZero { id, arg } => write!(f, "divide by zero at {}:{}", id, arg),
+            Self::DifferentBinding {
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "cannot bind {} to {}: already bound to {}",
+                name, given, expected
+            ),
+            Self::NonIntegerDimension { id, arg, axis, dim } => write!(
+                f,
+                "non-integer dimension at {}:{} axis {}: {}",
+                id, arg, axis, dim
+            ),
+            Self::DifferentDType {
+                id,
+                arg,
+                expected,
+                given,
+            } => write!(
+                f,
+                "different dtype at {}:{}: expected {}, given {}",
+                id, arg, expected, given
+            ),
+            Self::ReservedVariable { name } => {
+                write!(f, "variable {} is reserved for the dtypes", name)
+            }
+            Self::AxisOutOfRange { id, axis, rank } => write!(
+                f,
+                "axis {} is out of range at {}: the rank is {}",
+                axis, id, rank
+            ),
+        }
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnsupportedModel { id, model } => {
+                write!(f, "unsupported model at {}: {}", id, model)
+            }
+            Self::UnsupportedArgs { id } => {
+                write!(f, "multiple arguments are not supported at {}", id)
+            }
+            Self::NoVariableValue { id, name } => {
+                write!(f, "variable {} has no value at {}", name, id)
+            }
+            Self::UnresolvedDimension { id, dim } => {
+                write!(f, "unresolved dimension at {}: {}", id, dim)
+            }
+            Self::AxisOutOfRange { id, axis, rank } => write!(
+                f,
+                "axis {} is out of range at {}: the rank is {}",
+                axis, id, rank
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+#[cfg(feature = "ir")]
+impl fmt::Display for IrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::JsonError { error } => write!(f, "malformed IR: {}", error),
+            Self::UnsupportedVersion {
+                version: Some(version),
+            } => write!(
+                f,
+                "unsupported IR version: {} (expected {})",
+                version,
+                crate::ir::IR_VERSION
+            ),
+            Self::UnsupportedVersion { version: None } => write!(f, "IR version is not given"),
+        }
+    }
+}
+
+#[cfg(feature = "ir")]
+impl std::error::Error for IrError {}
@@ -1,13 +1,14 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use super::id::{GraphId, GraphIdArg};
 use super::node::Node;
-use super::shape::{Dim, DimKey, FitState, Shape, ShapeState, Shapes};
-use super::variable::{Value, ValueType, Variable};
+use super::shape::{floor, is_number, DType, Dim, DimKey, FitState, Shape, ShapeState, Shapes};
+use super::variable::{restore_sign, Value, ValueType, Variable};
 use crate::error::{CompileError, GraphError, NonExternModelError};
+use crate::warning::CompileWarning;
 
 use n3_parser::ast;
-use symengine::ExpressionMap;
+use symengine::{Expression, ExpressionMap};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
@@ -20,8 +21,53 @@ pub struct Graph {
 
     nodes: BTreeMap<GraphId, Node>,
     shape_state: ShapeState,
+    divisions: Vec<Division>,
 
     is_extern: bool,
+    /// The name of the std model which the graph is compiled from.
+    ///
+    /// The std models may have the behaviors which the grammar has no syntax for.
+    std_model: Option<String>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    errors: Recovery,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    is_poisoned: bool,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    warnings: Vec<CompileWarning>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    used_variables: HashSet<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    used_graphs: HashSet<String>,
+}
+
+/// The std models whose unset stride follows the kernel size.
+const STD_WINDOW_MODELS: &[&str] = &["MaxPool2d"];
+
+/// The std models which cast the inputs into the dtypes given to them, such as `Transform`.
+const STD_CAST_MODELS: &[&str] = &["Transform"];
+
+/// A quotient of the symbolic dimensions, which should be an integer once they are bound.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+struct Division {
+    id: GraphId,
+    arg: u64,
+    axis: usize,
+    dim: Dim,
+}
+
+/// The errors recovered so far, if recovering from errors.
+///
+/// The errors belong to the graph they have occurred in, so they are not cloned.
+#[derive(Debug, Default)]
+struct Recovery(Option<Vec<CompileError>>);
+
+impl Clone for Recovery {
+    fn clone(&self) -> Self {
+        Self(self.0.as_ref().map(|_| vec![]))
+    }
 }
 
 impl Graph {
@@ -33,7 +79,22 @@ impl Graph {
             graphs: HashMap::new(),
             nodes: BTreeMap::new(),
             shape_state: ShapeState::default(),
+            divisions: vec![],
             is_extern,
+            std_model: None,
+            errors: Recovery::default(),
+            is_poisoned: false,
+            warnings: vec![],
+            used_variables: HashSet::new(),
+            used_graphs: HashSet::new(),
+        }
+    }
+
+    /// Creates a placeholder of a graph which has failed to compile.
+    pub(crate) fn new_poisoned() -> Self {
+        Self {
+            is_poisoned: true,
+            ..Self::new(false)
         }
     }
 
@@ -45,7 +106,14 @@ impl Graph {
             graphs: self.graphs.clone(),
             nodes: BTreeMap::new(),
             shape_state: ShapeState::default(),
+            divisions: vec![],
             is_extern: false,
+            std_model: None,
+            errors: self.errors.clone(),
+            is_poisoned: false,
+            warnings: vec![],
+            used_variables: HashSet::new(),
+            used_graphs: HashSet::new(),
         }
     }
 }
@@ -55,6 +123,13 @@ impl Graph {
         self.is_extern
     }
 
+    /// Returns the name of the std model which the graph is compiled from, if any.
+    ///
+    /// The models of the same names declared by the users are not the std ones.
+    pub fn get_std_model(&self) -> Option<&str> {
+        self.std_model.as_deref()
+    }
+
     pub fn get_variables(&self) -> &HashMap<String, Variable> {
         &self.variables
     }
@@ -63,7 +138,34 @@ impl Graph {
         &self.nodes
     }
 
-    pub fn get_shapes(&self) -> BTreeMap<GraphId, Vec<Vec<Dim>>> {
+    pub fn get_warnings(&self) -> &[CompileWarning] {
+        &self.warnings
+    }
+
+    /// Returns the shapes of the output args of each node, with the batch axis at first
+    /// if `batch` is set, which is [`Dim::batch`] unless bound by [`Graph::instantiate`].
+    pub fn get_shapes(&self, batch: bool) -> BTreeMap<GraphId, Vec<Vec<Dim>>> {
+        if batch {
+            self.collect_shapes(Shape::unwrap_dims_with_batch)
+        } else {
+            self.collect_shapes(Shape::unwrap_dims)
+        }
+    }
+
+    /// Returns the dtypes of the output args of each node, if known.
+    pub fn get_dtypes(&self) -> BTreeMap<GraphId, BTreeMap<u64, DType>> {
+        self.nodes
+            .iter()
+            .map(|(id, node)| (*id, node.dtypes.clone()))
+            .collect()
+    }
+
+    /// Returns the shapes like `get_shapes`, with the values of the variables substituted.
+    pub(crate) fn get_resolved_shapes(&self, batch: bool) -> BTreeMap<GraphId, Vec<Vec<Dim>>> {
+        self.resolve_shapes(self.get_shapes(batch))
+    }
+
+    fn collect_shapes(&self, dims: fn(&Shape) -> &[Dim]) -> BTreeMap<GraphId, Vec<Vec<Dim>>> {
         self.nodes
             .iter()
             .map(|(id, node)| {
@@ -73,9 +175,10 @@ impl Graph {
                         .values()
                         .map(|s| match s {
                             Shape::Dynamic => unreachable!(),
-                            Shape::Fixed(dims) => {
-                                dims.iter().map(|d| self.eval_dim_for_output(d)).collect()
-                            }
+                            Shape::Fixed(_) => dims(s)
+                                .iter()
+                                .map(|d| self.eval_dim_for_output(d))
+                                .collect(),
                         })
                         .collect(),
                 };
@@ -83,6 +186,49 @@ impl Graph {
             })
             .collect()
     }
+
+    fn resolve_shapes(
+        &self,
+        shapes: BTreeMap<GraphId, Vec<Vec<Dim>>>,
+    ) -> BTreeMap<GraphId, Vec<Vec<Dim>>> {
+        shapes
+            .into_iter()
+            .map(|(id, shapes)| {
+                let shapes = shapes
+                    .iter()
+                    .map(|dims| {
+                        dims.iter()
+                            .map(|d| Dim::Expr(self.keys.eval_once(&d.to_expr())))
+                            .collect()
+                    })
+                    .collect();
+                (id, shapes)
+            })
+            .collect()
+    }
+
+    /// Returns a concrete copy of the graph, with the placeholders and the variables
+    /// bound to the given values, such as `H = 28`.
+    /// The batch axis can be bound as `batch`, unless a variable has the same name.
+    ///
+    /// Every dimension of the copy is evaluated to an integer.
+    pub fn instantiate(&self, values: &HashMap<String, u64>) -> Result<Self, GraphError> {
+        let mut values: Vec<_> = values.iter().collect();
+        values.sort();
+
+        let mut graph = self.clone();
+        let mut placeholders = vec![];
+        for (name, value) in values {
+            match graph.bind(name, *value)? {
+                key @ DimKey::Placeholder(..) | key @ DimKey::Batch => {
+                    placeholders.push((key, *value))
+                }
+                DimKey::Variable(_) => {}
+            }
+        }
+        graph.concretize(&placeholders)?;
+        Ok(graph)
+    }
 }
 
 impl Graph {
@@ -91,6 +237,15 @@ impl Graph {
         alias: Option<String>,
         variable: Variable,
     ) -> Result<(), GraphError> {
+        // the dtypes are given as the keywords, so their names are reserved
+        let reserved = alias
+            .iter()
+            .chain(Some(&variable.description))
+            .find(|name| DType::from_name(name).is_some());
+        if let Some(name) = reserved {
+            return Err(GraphError::ReservedVariable { name: name.clone() });
+        }
+
         let name = &variable.description;
         if self.variables.contains_key(name) {
             if let Some(value) = variable.unwrap_uint() {
@@ -144,12 +299,73 @@ impl Graph {
         }
     }
 
+    pub(crate) fn set_std_model(&mut self, name: String) {
+        self.std_model = Some(name);
+    }
+
     pub(crate) fn add_graph(&mut self, name: String, graph: Self) {
         self.graphs.insert(name, graph);
     }
 
     pub(crate) fn find_graph(&mut self, name: &str) -> Option<Self> {
-        self.graphs.get(name).cloned()
+        self.graphs.get(name).cloned().map(|mut graph| {
+            // the warnings belong to the original graph
+            graph.warnings.clear();
+            graph
+        })
+    }
+
+    pub(crate) fn has_graph(&self, name: &str) -> bool {
+        self.graphs.contains_key(name)
+    }
+
+    pub(crate) fn is_graph_used(&self, name: &str) -> bool {
+        self.used_graphs.contains(name)
+    }
+
+    /// Marks the graph as used, if the value refers to it.
+    pub(crate) fn use_value(&mut self, value: &Value) {
+        if let Value::Model(name) = value {
+            self.used_graphs.insert(name.clone());
+        }
+    }
+
+    pub(crate) fn warn(&mut self, warning: CompileWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// Checks whether the variable would be overridden to the value it already has.
+    pub(crate) fn check_redundant_variable(
+        &self,
+        model: &str,
+        name: &str,
+        value: &Value,
+    ) -> Option<CompileWarning> {
+        let variable = match self.variable_aliases.get(name) {
+            Some(name) => self.variables.get(name),
+            None => self.variables.get(name),
+        };
+        if variable.and_then(|v| v.value.as_ref()) == Some(value) {
+            Some(CompileWarning::RedundantVariable {
+                model: model.to_string(),
+                variable: name.to_string(),
+                value: value.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Warns the variables which are not used by any node.
+    pub(crate) fn check_unused_variables(&mut self, model: &str, names: &[String]) {
+        for name in names {
+            if !self.used_variables.contains(name) {
+                self.warn(CompileWarning::UnusedVariable {
+                    model: model.to_string(),
+                    variable: name.clone(),
+                });
+            }
+        }
     }
 
     pub(crate) fn attach(
@@ -159,6 +375,11 @@ impl Graph {
         graph: Option<Self>,
         args: Vec<ast::GraphPassArg>,
     ) -> Result<(), CompileError> {
+        let (dtypes, args) = match split_dtypes(args) {
+            Ok(args) => args,
+            Err(error) => return Err(CompileError::GraphError { error, model: name }),
+        };
+
         let last_id = if self.nodes.is_empty() {
             if id.is_input() {
                 None
@@ -220,7 +441,7 @@ impl Graph {
                     ..Default::default()
                 }
             }
-            Node::INTRINSIC_IDENTITY => {
+            Node::INTRINSIC_IDENTITY | Node::INTRINSIC_BROADCAST | Node::INTRINSIC_CONCAT => {
                 if last_id.is_some() {
                     // assume that the input has full fixed shapes
                     self.shape_state = ShapeState::Fixed(FitState::Full);
@@ -252,6 +473,7 @@ impl Graph {
                 } else if let Some(graph) = graph {
                     self.attach_model(id, name, graph, args)?
                 } else if let Some(graph) = self.graphs.get(&name).cloned() {
+                    self.used_graphs.insert(name.clone());
                     self.attach_model(id, name, graph, args)?
                 } else {
                     return Err(CompileError::NonExternModelError {
@@ -268,11 +490,27 @@ impl Graph {
             node.inputs = inputs.into_iter().collect();
         }
 
+        node.dtypes = match self.infer_dtypes(id, &node, dtypes) {
+            Ok(dtypes) => dtypes,
+            Err(error) => {
+                return Err(CompileError::GraphError {
+                    error,
+                    model: node.name,
+                })
+            }
+        };
+
         self.nodes.insert(id, node);
         Ok(())
     }
 
     pub(crate) fn adjust_shapes(&mut self, shapes: ast::Shapes) -> Result<(), CompileError> {
+        // the declared shapes follow the batch axis of the inferred ones
+        let batch = match self.nodes.values().next_back() {
+            Some(node) => node.shapes.batch(),
+            None => Dim::batch(),
+        };
+
         let mut is_new_var_created = false;
         let shapes = shapes
             .0
@@ -294,10 +532,11 @@ impl Graph {
                 let shape = shape
                     .0
                     .into_iter()
-                    .map(|d| self.convert_dim(d, arg, &mut is_new_var_created))
+                    .enumerate()
+                    .map(|(axis, d)| self.convert_dim(d, arg, axis, &mut is_new_var_created))
                     .collect::<Result<_, _>>();
                 match shape {
-                    Ok(shape) => Ok((arg, Shape::Fixed(shape))),
+                    Ok(shape) => Ok((arg, Shape::with_batch(batch.clone(), shape))),
                     Err(error) => Err(CompileError::GraphError {
                         error,
                         model: self.get_last_node_name().to_string(),
@@ -317,6 +556,7 @@ impl Graph {
             last_shapes = last_shapes.product();
         }
 
+        let mut floors = vec![];
         match shapes.validate_args_rank(&last_shapes, &id) {
             Ok(true) => {
                 for ((&arg, last_shape), shape) in last_shapes
@@ -330,6 +570,9 @@ impl Graph {
                         if let Err(error) = self.update_dim(id, arg, last_dim, dim, axis) {
                             return Err(CompileError::GraphError { error, model });
                         }
+                        if let Dim::Floor { .. } = last_dim {
+                            floors.push((last_dim.clone(), dim.clone()));
+                        }
                     }
                 }
             }
@@ -337,6 +580,24 @@ impl Graph {
             Err(error) => return Err(CompileError::GraphError { error, model }),
         }
 
+        // the passes of the node share the declared dims of the floors proven above
+        for (pass_id, node) in &mut self.nodes {
+            if pass_id.node != id.node || *pass_id == id {
+                continue;
+            }
+            if let Shapes::Fixed(pass_shapes) = &mut node.shapes {
+                for pass_shape in pass_shapes.values_mut() {
+                    if let Shape::Fixed(pass_dims) = pass_shape {
+                        for pass_dim in pass_dims {
+                            if let Some((_, dim)) = floors.iter().find(|(f, _)| f == pass_dim) {
+                                *pass_dim = dim.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
         self.set_last_shapes(shapes_to);
         self.shape_state = ShapeState::Fixed(if is_new_var_created {
             FitState::Weak
@@ -348,6 +609,98 @@ impl Graph {
 }
 
 impl Graph {
+    pub(crate) fn start_recovery(&mut self) {
+        self.errors = Recovery(Some(vec![]));
+    }
+
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.errors.0.is_some()
+    }
+
+    pub(crate) fn is_poisoned(&self) -> bool {
+        self.is_poisoned
+    }
+
+    /// Records the error if recovering from errors, otherwise returns it back.
+    pub(crate) fn recover(&mut self, error: CompileError) -> Result<(), CompileError> {
+        match &mut self.errors.0 {
+            Some(errors) => {
+                errors.push(error);
+                self.is_poisoned = true;
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Moves the recovered errors and the warnings of the child into this graph.
+    pub(crate) fn merge_errors(&mut self, child: &mut Self) {
+        if let (Some(errors), Some(child_errors)) = (&mut self.errors.0, &mut child.errors.0) {
+            errors.append(child_errors);
+        }
+        self.is_poisoned |= child.is_poisoned;
+
+        self.warnings.append(&mut child.warnings);
+        self.used_graphs.extend(child.used_graphs.drain());
+    }
+
+    /// Marks the recovered errors as occurred in the graph of the model, if not yet located.
+    pub(crate) fn locate_errors(&mut self, model: &str) {
+        if let Some(errors) = &mut self.errors.0 {
+            *errors = errors.drain(..).map(|e| e.in_model(model)).collect();
+        }
+    }
+
+    pub(crate) fn take_errors(&mut self) -> Vec<CompileError> {
+        self.errors
+            .0
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Attaches a node which has failed to compile,
+    /// so that the following nodes can be compiled with its declared shapes.
+    pub(crate) fn attach_poisoned(&mut self, id: GraphId, name: String) {
+        if let Some(last_id) = self.nodes.keys().next_back() {
+            if *last_id >= id {
+                return;
+            }
+        }
+        let inputs = self
+            .nodes
+            .keys()
+            .next_back()
+            .map(|id| vec![GraphIdArg::with_id(*id)])
+            .unwrap_or_default();
+
+        self.nodes
+            .insert(id, Self::poisoned_node(name, None, inputs));
+        self.shape_state = ShapeState::Required(FitState::Weak);
+        self.is_poisoned = true;
+    }
+
+    /// Drops the inferred shapes of the last node which has failed to fit its declared shapes.
+    pub(crate) fn poison_last_node(&mut self) {
+        if let Some(node) = self.nodes.values_mut().next_back() {
+            node.shapes = Shapes::Dynamic;
+            node.poisoned = true;
+        }
+        self.shape_state = ShapeState::Required(FitState::Weak);
+        self.is_poisoned = true;
+    }
+
+    fn poisoned_node(name: String, graph: Option<Self>, inputs: Vec<GraphIdArg>) -> Node {
+        Node {
+            name,
+            graph,
+            inputs,
+            shapes: Shapes::Dynamic,
+            dtypes: BTreeMap::new(),
+            poisoned: true,
+        }
+    }
+
     pub(crate) fn finalize(&mut self) -> Result<(), CompileError> {
         self.graphs.clear();
         match self.shape_state {
@@ -392,7 +745,17 @@ impl Graph {
                         }
                     }
                 }
+                // the variables of broken graphs are unknown
+                ast::GraphPassArg::Keyword { .. } if graph.is_poisoned() => {}
                 ast::GraphPassArg::Keyword { name, value } => {
+                    let value = restore_sign(value);
+                    self.use_value(&value);
+                    if let Some(warning) =
+                        graph.check_redundant_variable(&model_name, &name, &value)
+                    {
+                        self.warn(warning);
+                    }
+
                     let ty = ValueType::new(Some(&value), false);
                     if let Err(error) = graph.update_variable(None, Some(name), value, ty) {
                         return Err(CompileError::GraphError {
@@ -404,6 +767,23 @@ impl Graph {
             }
         }
 
+        // do not estimate shapes from the broken ones
+        let input_ids = if inputs.is_empty() {
+            vec![*self.get_last_node_id()]
+        } else {
+            inputs.iter().map(|input| input.id).collect()
+        };
+        let is_input_poisoned = input_ids.iter().any(|id| {
+            let node = &self.nodes[id];
+            node.poisoned && matches!(node.shapes, Shapes::Dynamic)
+        });
+        if graph.is_poisoned() || is_input_poisoned {
+            self.shape_state = ShapeState::Required(FitState::Weak);
+            self.is_poisoned = true;
+            return Ok(Self::poisoned_node(model_name, Some(graph), inputs));
+        }
+
+        graph.fill_default_stride();
         let shapes = match self.apply_shapes_as_input(&mut graph, &inputs, id) {
             Ok(shapes) => shapes,
             Err(error) => {
@@ -420,9 +800,41 @@ impl Graph {
             graph: Some(graph),
             inputs,
             shapes,
+            dtypes: BTreeMap::new(),
+            poisoned: false,
         })
     }
 
+    /// Lets the unset stride of the std window models follow the kernel size, as `MaxPool2d`
+    /// of PyTorch does, since the models can declare only values as defaults.
+    fn fill_default_stride(&mut self) {
+        let is_window = self
+            .get_std_model()
+            .map(|name| STD_WINDOW_MODELS.contains(&name))
+            .unwrap_or(false);
+        if !is_window {
+            return;
+        }
+
+        let is_stride_unset = self
+            .variables
+            .get("stride")
+            .map(|v| v.value.is_none())
+            .unwrap_or(false);
+        let kernel_size = self
+            .variables
+            .get("kernel size")
+            .and_then(|v| v.unwrap_uint());
+
+        if let (true, Some(kernel_size)) = (is_stride_unset, kernel_size) {
+            let stride = self.variables.get_mut("stride").unwrap();
+            stride.value = Some(Value::UInt(kernel_size));
+            stride.ty = ValueType::UInt;
+            self.keys
+                .insert(DimKey::Variable("stride".to_string()), kernel_size);
+        }
+    }
+
     fn apply_shapes_as_input(
         &mut self,
         target: &mut Self,
@@ -433,6 +845,8 @@ impl Graph {
         let target_shapes = target.get_first_shapes().clone();
 
         if target_shapes.validate_args_rank(&shapes, &id)? {
+            // the outputs follow the batch axis of the inputs
+            let batch = shapes.batch();
             let shapes = shapes
                 .unwrap_shapes()
                 .iter()
@@ -449,7 +863,7 @@ impl Graph {
                         })
                         .collect::<Result<_, _>>()?;
 
-                    Ok((arg, Shape::Fixed(shape)))
+                    Ok((arg, Shape::with_batch(batch.clone(), shape)))
                 })
                 .collect::<Result<_, _>>()?;
 
@@ -461,9 +875,10 @@ impl Graph {
                         let shape = shape
                             .unwrap_dims()
                             .iter()
-                            .map(|d| target.eval_dim(d))
-                            .collect();
-                        Ok((*arg, Shape::Fixed(shape)))
+                            .enumerate()
+                            .map(|(axis, d)| check_integer(id, *arg, axis, target.eval_dim(d)))
+                            .collect::<Result<_, _>>()?;
+                        Ok((*arg, Shape::with_batch(batch.clone(), shape)))
                     })
                     .collect::<Result<_, _>>()?,
             };
@@ -474,47 +889,206 @@ impl Graph {
 
             Ok(shapes)
         } else if let Shapes::Dynamic = target_shapes {
-            Ok(shapes)
+            match target.get_last_node_name() {
+                Node::INTRINSIC_BROADCAST => self.broadcast_shapes(shapes, id),
+                Node::INTRINSIC_CONCAT => {
+                    let axis = match target.variables.get("axis").and_then(|v| v.value.as_ref()) {
+                        Some(Value::Int(axis)) => *axis,
+                        Some(Value::UInt(axis)) => *axis as i64,
+                        _ => {
+                            return Err(GraphError::NoVariableValue {
+                                name: "axis".to_string(),
+                            })
+                        }
+                    };
+                    self.concat_shapes(shapes, id, axis)
+                }
+                _ => Ok(shapes),
+            }
         // dynamic inputs
-        } else if inputs.is_empty() && id.is_first() {
-            self.set_last_shapes_from_child(target_shapes, id)
+        } else if self.is_dynamic_input(inputs, id) {
+            self.set_input_shapes_from_child(target_shapes, id)
         } else {
-            unimplemented!()
+            // only the input node can be given the shapes from the children
+            let input = match inputs {
+                [input] => input.id,
+                _ => id,
+            };
+            Err(GraphError::FullShapeRequired { id: input })
         }
     }
 
+    /// Merges the shapes of the args into one, aligning their trailing axes.
+    fn broadcast_shapes(&self, shapes: Shapes, id: GraphId) -> Result<Shapes, GraphError> {
+        let shapes = match shapes {
+            Shapes::Dynamic => return Ok(shapes),
+            Shapes::Fixed(shapes) => shapes,
+        };
+        let batch = Shapes::Fixed(shapes.clone()).batch();
+
+        let mut dims: Vec<Dim> = vec![];
+        for (arg, shape) in shapes {
+            let shape = match shape {
+                Shape::Dynamic => return Ok(Shapes::Dynamic),
+                Shape::Fixed(_) => shape.unwrap_dims().to_vec(),
+            };
+
+            let rank = dims.len().max(shape.len());
+            let pad = |dims: Vec<Dim>| {
+                let ones = vec![Dim::Expr(1u64.into()); rank - dims.len()];
+                ones.into_iter().chain(dims).collect::<Vec<_>>()
+            };
+            dims = pad(dims)
+                .into_iter()
+                .zip(pad(shape))
+                .enumerate()
+                .map(|(axis, (dim, given))| {
+                    let (expected, given) = (self.eval_dim(&dim), self.eval_dim(&given));
+                    if expected.to_expr() == given.to_expr() || given.to_expr() == 1u64 {
+                        Ok(dim)
+                    } else if expected.to_expr() == 1u64 {
+                        Ok(given)
+                    } else {
+                        Err(GraphError::DifferentDimension {
+                            id,
+                            arg,
+                            axis,
+                            expected,
+                            given,
+                        })
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(Shapes::Fixed(
+            vec![(0, Shape::with_batch(batch, dims))]
+                .into_iter()
+                .collect(),
+        ))
+    }
+
+    /// Merges the shapes of the args into one, summing the dims of the axis.
+    ///
+    /// The negative axis counts from the last one.
+    fn concat_shapes(&self, shapes: Shapes, id: GraphId, axis: i64) -> Result<Shapes, GraphError> {
+        let shapes = match shapes {
+            Shapes::Dynamic => return Ok(shapes),
+            Shapes::Fixed(shapes) => shapes,
+        };
+        let batch = Shapes::Fixed(shapes.clone()).batch();
+
+        let mut dims: Option<Vec<Dim>> = None;
+        for (arg, shape) in shapes {
+            let shape = match shape {
+                Shape::Dynamic => return Ok(Shapes::Dynamic),
+                Shape::Fixed(_) => shape.unwrap_dims().to_vec(),
+            };
+            let rank = shape.len();
+            let concat_axis = if axis < 0 { axis + rank as i64 } else { axis };
+            if concat_axis < 0 || concat_axis >= rank as i64 {
+                return Err(GraphError::AxisOutOfRange { id, axis, rank });
+            }
+            let concat_axis = concat_axis as usize;
+
+            dims = Some(match dims {
+                None => shape,
+                Some(dims) if dims.len() != shape.len() => {
+                    return Err(GraphError::DifferentRank {
+                        id,
+                        arg,
+                        last_rank: dims.len(),
+                        rank: shape.len(),
+                    })
+                }
+                Some(dims) => dims
+                    .into_iter()
+                    .zip(shape)
+                    .enumerate()
+                    .map(|(dim_axis, (dim, given))| {
+                        if dim_axis == concat_axis {
+                            return Ok(Dim::Expr(self.eval_dim(&(dim + given)).to_expr()));
+                        }
+                        let (expected, given) = (self.eval_dim(&dim), self.eval_dim(&given));
+                        if expected.to_expr() == given.to_expr() {
+                            Ok(dim)
+                        } else {
+                            Err(GraphError::DifferentDimension {
+                                id,
+                                arg,
+                                axis: dim_axis,
+                                expected,
+                                given,
+                            })
+                        }
+                    })
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+        let dims = dims.unwrap_or_default();
+        Ok(Shapes::Fixed(
+            vec![(0, Shape::with_batch(batch, dims))]
+                .into_iter()
+                .collect(),
+        ))
+    }
+
     fn convert_dim(
         &mut self,
         dim: ast::Dim,
         arg: u64,
+        axis: usize,
         is_new_var_created: &mut bool,
     ) -> Result<Dim, GraphError> {
         match dim {
             ast::Dim::Fixed(dim) => Ok(Dim::Expr(dim.into())),
             ast::Dim::Semantic(var) => self.find_var(var, is_new_var_created),
             ast::Dim::Expr { lhs, rhs, op } => {
-                let lhs = self.convert_dim(*lhs, arg, is_new_var_created)?;
-                let rhs = self.convert_dim(*rhs, arg, is_new_var_created)?;
+                let lhs = self.convert_dim(*lhs, arg, axis, is_new_var_created)?;
+                let rhs = self.convert_dim(*rhs, arg, axis, is_new_var_created)?;
                 match op {
                     ast::DimOp::Add => Ok(lhs + rhs),
                     ast::DimOp::Sub => Ok(lhs - rhs),
                     ast::DimOp::Mul => Ok(lhs * rhs),
                     ast::DimOp::Div => {
-                        if let Dim::Expr(rhs) = self.eval_dim(&rhs) {
-                            if rhs == 0u64 {
-                                return Err(GraphError::DivideByZero {
-                                    id: *self.get_last_node_id(),
-                                    arg,
-                                });
+                        self.check_divisor(&rhs, arg)?;
+
+                        // the extern models follow the frameworks
+                        if self.is_extern {
+                            return Ok(lhs.floor_div(rhs));
+                        }
+
+                        let id = *self.get_last_node_id();
+                        let dim = lhs / rhs;
+                        match self.eval_dim(&dim) {
+                            Dim::Expr(expr) if is_number(&expr) => {
+                                check_integer(id, arg, axis, Dim::Expr(expr))?;
                             }
+                            // test it once the placeholders are bound
+                            _ => self.divisions.push(Division {
+                                id,
+                                arg,
+                                axis,
+                                dim: dim.clone(),
+                            }),
                         }
-                        Ok(lhs / rhs)
+                        Ok(dim)
                     }
                 }
             }
         }
     }
 
+    /// Tests whether the divisor is not zero, if it is concrete.
+    fn check_divisor(&self, rhs: &Dim, arg: u64) -> Result<(), GraphError> {
+        match self.eval_dim(rhs) {
+            Dim::Expr(rhs) if rhs == 0u64 => Err(GraphError::DivideByZero {
+                id: *self.get_last_node_id(),
+                arg,
+            }),
+            _ => Ok(()),
+        }
+    }
+
     fn find_var(&mut self, var: String, is_new_var_created: &mut bool) -> Result<Dim, GraphError> {
         let key = DimKey::Placeholder(var, true);
         if self.keys.contains_key(&key) {
@@ -530,6 +1104,7 @@ impl Graph {
         }
         if let Some(graph_var) = self.variables.get_mut(&var) {
             graph_var.expect_or_default(ValueType::UInt)?;
+            self.used_variables.insert(var.clone());
             let key = DimKey::Variable(var);
             Ok(Dim::Key(key))
         } else if self.shape_state.is_new_var_available() {
@@ -545,6 +1120,112 @@ impl Graph {
         }
     }
 
+    fn bind(&mut self, name: &str, value: u64) -> Result<DimKey, GraphError> {
+        let different_binding = |expected| GraphError::DifferentBinding {
+            name: name.to_string(),
+            expected: Dim::Expr(expected),
+            given: value,
+        };
+
+        let key = DimKey::Placeholder(name.to_string(), true);
+        let key = if self.keys.contains_key(&key) {
+            key
+        } else {
+            DimKey::Placeholder(name.to_string(), false)
+        };
+        if let Some(expected) = self.keys.get(&key) {
+            // the placeholders may be bound to the other placeholders
+            let is_fixed = expected.as_str().parse::<f64>().is_ok();
+            if is_fixed && expected != value {
+                return Err(different_binding(expected));
+            }
+        } else {
+            let name = match self.variable_aliases.get(name) {
+                Some(name) => name.clone(),
+                None => name.to_string(),
+            };
+            let variable = match self.variables.get_mut(&name) {
+                Some(variable) => variable,
+                None if Dim::batch().to_string() == name => {
+                    self.keys.insert(DimKey::Batch, value);
+                    return Ok(DimKey::Batch);
+                }
+                None => return Err(GraphError::NoSuchVariable { name }),
+            };
+            if let Some(expected) = variable.unwrap_uint() {
+                if expected != value {
+                    return Err(different_binding(expected.into()));
+                }
+            }
+            variable.update(Value::UInt(value), ValueType::UInt)?;
+            self.used_variables.insert(name.clone());
+            self.keys.insert(DimKey::Variable(name.clone()), value);
+            return Ok(DimKey::Variable(name));
+        }
+
+        self.keys.insert(key.clone(), value);
+        Ok(key)
+    }
+
+    /// Evaluates all the shapes into integers, including the ones of the non-extern children.
+    fn concretize(&mut self, placeholders: &[(DimKey, u64)]) -> Result<(), GraphError> {
+        // the children share the placeholders of the parents
+        for (key, value) in placeholders {
+            self.keys.insert(key.clone(), *value);
+        }
+
+        for division in &self.divisions {
+            let dim = self
+                .keys
+                .eval_once(&self.keys.eval_once(&division.dim.to_expr()));
+            if is_number(&dim) {
+                check_integer(division.id, division.arg, division.axis, Dim::Expr(dim))?;
+            }
+        }
+
+        for (id, shapes) in self.get_resolved_shapes(true) {
+            let shapes = shapes
+                .into_iter()
+                .enumerate()
+                .map(|(arg, dims)| {
+                    let arg = arg as u64;
+                    // the batch axis may be left unbound
+                    let (batch, dims) = dims.split_first().unwrap();
+                    let batch = match to_uint(&batch.to_expr()) {
+                        Some(batch) => Dim::Expr(batch.into()),
+                        None => Dim::batch(),
+                    };
+                    let dims = dims
+                        .iter()
+                        .enumerate()
+                        .map(|(axis, dim)| match to_uint(&dim.to_expr()) {
+                            Some(dim) => Ok(Dim::Expr(dim.into())),
+                            None if is_number(&dim.to_expr()) => {
+                                Err(GraphError::NonIntegerDimension {
+                                    id,
+                                    arg,
+                                    axis,
+                                    dim: dim.clone(),
+                                })
+                            }
+                            None => Err(GraphError::CannotEstimateShape { id, arg, axis }),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok((arg, Shape::with_batch(batch, dims)))
+                })
+                .collect::<Result<_, _>>()?;
+
+            let node = self.nodes.get_mut(&id).unwrap();
+            node.shapes = Shapes::Fixed(shapes);
+            if let Some(child) = &mut node.graph {
+                if !child.is_extern() {
+                    child.concretize(placeholders)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn eval_dim(&self, dim: &Dim) -> Dim {
         Self::eval_dim_with_keys(&self.keys, dim)
     }
@@ -553,6 +1234,10 @@ impl Graph {
         match dim {
             Dim::Key(key) => match key {
                 DimKey::Placeholder(_, false) => Dim::Expr(self.keys.eval_once(&key.to_expr())),
+                DimKey::Batch => match self.keys.get(key) {
+                    Some(batch) => Dim::Expr(batch.clone()),
+                    None => dim.clone(),
+                },
                 _ => dim.clone(),
             },
             _ => dim.clone(),
@@ -562,6 +1247,9 @@ impl Graph {
     fn eval_dim_with_keys(keys: &ExpressionMap<DimKey>, dim: &Dim) -> Dim {
         match dim {
             Dim::Key(DimKey::Placeholder(_, false)) => dim.clone(),
+            Dim::Floor { quotient, offset } => {
+                Dim::new_floor(keys.eval_once(quotient), keys.eval_once(offset))
+            }
             _ => Dim::Expr(keys.eval_once(&dim.to_expr())),
         }
     }
@@ -620,7 +1308,7 @@ impl Graph {
                 let ground = ground.clone();
                 let ground_eval = self.eval_dim(&ground);
 
-                if dim == ground_eval {
+                if dim == ground_eval || is_floor_of(&dim, &ground_eval) {
                     Ok(ground)
                 } else {
                     Err(GraphError::DifferentDimension {
@@ -635,6 +1323,90 @@ impl Graph {
         }
     }
 
+    /// Propagates the dtypes of the inputs to the outputs of the node.
+    ///
+    /// The nodes keep the dtype of their first input. The extern models may declare the dtypes
+    /// of their inputs, such as `#0 fixed (f32 = 0)`, where a float dtype accepts any float.
+    /// The given dtypes should be the propagated ones, unless the model has `cast` set.
+    fn infer_dtypes(
+        &self,
+        id: GraphId,
+        node: &Node,
+        declared: BTreeMap<u64, DType>,
+    ) -> Result<BTreeMap<u64, DType>, GraphError> {
+        let inputs: Vec<_> = node
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                let arg = input.arg.unwrap_or(0);
+                let dtype = self.nodes.get(&input.id)?.dtypes.get(&arg)?;
+                Some((input.id, arg, *dtype))
+            })
+            .collect();
+        let identity = || {
+            inputs
+                .first()
+                .map(|(_, _, dtype)| (0, *dtype))
+                .into_iter()
+                .collect()
+        };
+
+        let mut dtypes: BTreeMap<_, _> = match &node.graph {
+            Some(graph) if graph.is_extern() => {
+                let expected = graph.nodes.values().next().map(|input| &input.dtypes);
+                for (index, (input, arg, given)) in inputs.iter().enumerate() {
+                    let expected = match expected.and_then(|d| d.get(&(index as u64))) {
+                        Some(expected) => *expected,
+                        None => continue,
+                    };
+                    if *given != expected && !(given.is_float() && expected.is_float()) {
+                        return Err(GraphError::DifferentDType {
+                            id: *input,
+                            arg: *arg,
+                            expected,
+                            given: *given,
+                        });
+                    }
+                }
+                identity()
+            }
+            Some(graph) => match graph.nodes.values().next_back() {
+                Some(last) if !last.dtypes.is_empty() => last.dtypes.clone(),
+                _ => identity(),
+            },
+            None => identity(),
+        };
+
+        let is_cast = node.graph.as_ref().map(Self::is_cast).unwrap_or(false);
+        for (arg, dtype) in declared {
+            match dtypes.get(&arg) {
+                Some(given) if *given != dtype && !is_cast => {
+                    return Err(GraphError::DifferentDType {
+                        id,
+                        arg,
+                        expected: dtype,
+                        given: *given,
+                    });
+                }
+                _ => {
+                    dtypes.insert(arg, dtype);
+                }
+            }
+        }
+        Ok(dtypes)
+    }
+
+    fn has_variable(&self, name: &str) -> bool {
+        self.variables.contains_key(name) || self.variable_aliases.contains_key(name)
+    }
+
+    /// Returns whether the model casts the inputs into the declared dtypes.
+    fn is_cast(&self) -> bool {
+        self.get_std_model()
+            .map(|name| STD_CAST_MODELS.contains(&name))
+            .unwrap_or(false)
+    }
+
     fn get_last_node_id(&self) -> &GraphId {
         self.nodes.iter().rev().next().unwrap().0
     }
@@ -700,7 +1472,22 @@ impl Graph {
         self.nodes.iter_mut().rev().next().unwrap().1.shapes = shapes;
     }
 
-    fn set_last_shapes_from_child(
+    /// Tests whether the node takes the whole input node, which has no shapes yet.
+    fn is_dynamic_input(&self, inputs: &[GraphIdArg], id: GraphId) -> bool {
+        let input = GraphId::new_input();
+        let is_input = match inputs {
+            [] => id.is_first(),
+            [GraphIdArg { id, arg }] => *id == input && arg.unwrap_or(0) == 0,
+            _ => false,
+        };
+        is_input
+            && matches!(
+                self.nodes.get(&input).map(|node| &node.shapes),
+                Some(Shapes::Dynamic)
+            )
+    }
+
+    fn set_input_shapes_from_child(
         &mut self,
         mut shapes: Shapes,
         id: GraphId,
@@ -712,13 +1499,88 @@ impl Graph {
                     self.find_var(ph, &mut false)?;
                 }
 
-                self.set_last_shapes(shapes.clone());
+                let input = self.nodes.get_mut(&GraphId::new_input()).unwrap();
+                input.shapes = shapes.clone();
                 Ok(shapes)
             }
         }
     }
 }
 
+/// Converts the expression into an integer, if it is an integer.
+fn to_uint(expr: &Expression) -> Option<u64> {
+    let expr = expr.as_str();
+    match expr.parse() {
+        Ok(value) => Some(value),
+        // the products of shapes are evaluated in real numbers
+        Err(_) => match expr.parse::<f64>() {
+            Ok(value) if value >= 0.0 && value.fract() == 0.0 => Some(value as u64),
+            _ => None,
+        },
+    }
+}
+
+/// Tests whether the dimension rounded down, such as `floor((H - 1)/2) + 1`, is the given one,
+/// such as `H/2`.
+///
+/// The given dimensions are integers, so it holds if the offset is an integer, and the quotient
+/// is larger than the given one without the offset by less than 1. The other cases cannot be
+/// proven.
+fn is_floor_of(floor_dim: &Dim, dim: &Dim) -> bool {
+    match floor_dim {
+        Dim::Floor { quotient, offset } if floor(offset) == *offset => {
+            let diff = quotient.clone() - dim.to_expr() + offset.clone();
+            is_number(&diff) && floor(&diff) == 0u64
+        }
+        _ => false,
+    }
+}
+
+/// Tests whether the dimension is an integer, if it is a number.
+fn check_integer(id: GraphId, arg: u64, axis: usize, dim: Dim) -> Result<Dim, GraphError> {
+    match &dim {
+        Dim::Expr(expr) if is_number(expr) && to_uint(expr).is_none() => {
+            Err(GraphError::NonIntegerDimension { id, arg, axis, dim })
+        }
+        _ => Ok(dim),
+    }
+}
+
+/// Splits the dtypes of the output args from the other arguments.
+///
+/// The grammar of n3-parser has no syntax for the dtypes, so they are written as the keywords
+/// named after the dtypes, whose values are the output args, such as `#0 Input (i64 = 0)` or
+/// `#0 fixed (f32 = 0)` in the extern models. Thus the names of the dtypes are reserved, and
+/// the variables named after them are reported as `ReservedVariable`.
+fn split_dtypes(
+    args: Vec<ast::GraphPassArg>,
+) -> Result<(BTreeMap<u64, DType>, Vec<ast::GraphPassArg>), GraphError> {
+    let mut dtypes = BTreeMap::new();
+    let mut others = vec![];
+    for arg in args {
+        let dtype = match &arg {
+            ast::GraphPassArg::Keyword { name, .. } => DType::from_name(name),
+            _ => None,
+        };
+        match (dtype, arg) {
+            (Some(dtype), ast::GraphPassArg::Keyword { value, .. }) => match value {
+                Value::UInt(arg) => {
+                    dtypes.insert(arg, dtype);
+                }
+                other => {
+                    return Err(GraphError::DifferentVariableType {
+                        variable: dtype.to_string(),
+                        expected: ValueType::UInt,
+                        given: Some(other),
+                    })
+                }
+            },
+            (_, arg) => others.push(arg),
+        }
+    }
+    Ok((dtypes, others))
+}
+
 fn get_flag(args: &[ast::GraphPassArg]) -> Result<bool, GraphError> {
     args.iter()
         .find(|a| a.is_named("transform"))
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct GraphIdArg {
@@ -11,7 +13,9 @@ impl GraphIdArg {
     }
 }
 
+/// Serialized as a string such as `#1.1.0` in human-readable formats, to be the key of maps.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self"))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct GraphId {
     pub node: u64,
@@ -57,4 +61,63 @@ impl GraphId {
     pub fn is_first(&self) -> bool {
         self == &Self::new_first()
     }
+
+    #[cfg(feature = "serde")]
+    fn parse(id: &str) -> Option<Self> {
+        let mut values = id.strip_prefix('#')?.split('.').map(|v| v.parse().ok());
+        let node = values.next()??;
+        match (values.next(), values.next(), values.next()) {
+            (None, None, None) => Some(Self {
+                node,
+                pass: 0,
+                repeat: 0,
+            }),
+            (Some(pass), Some(repeat), None) => Some(Self {
+                node,
+                pass: pass?,
+                repeat: repeat?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GraphId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.pass == 0 && self.repeat == 0 {
+            write!(f, "#{}", self.node)
+        } else {
+            write!(f, "#{}.{}.{}", self.node, self.pass, self.repeat)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GraphId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            Self::serialize(self, serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GraphId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let id = <String as serde::Deserialize>::deserialize(deserializer)?;
+            Self::parse(&id)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid graph id: {}", id)))
+        } else {
+            Self::deserialize(deserializer)
+        }
+    }
 }
@@ -8,6 +8,7 @@ mod variable;
 pub use self::graph::Graph;
 pub use self::id::{GraphId, GraphIdArg};
 pub use self::node::Node;
-pub use self::root::GraphRoot;
-pub use self::shape::{Dim, DimKey};
+pub use self::root::{GraphRoot, ReloadReport};
+pub use self::shape::{DType, Dim, DimKey};
+pub(crate) use self::variable::restore_sign;
 pub use self::variable::{Value, ValueType, Variable};
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use super::graph::Graph;
 use super::id::GraphIdArg;
-use super::shape::Shapes;
+use super::shape::{DType, Shapes};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
@@ -9,12 +11,21 @@ pub struct Node {
     pub graph: Option<Graph>,
     pub inputs: Vec<GraphIdArg>,
     pub(crate) shapes: Shapes,
+    /// The dtypes of the output args, which are unknown if not given.
+    pub(crate) dtypes: BTreeMap<u64, DType>,
+    /// Whether the node has failed to compile while recovering from errors.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) poisoned: bool,
 }
 
 impl Node {
     pub const INTRINSIC_DYNAMIC: &'static str = "dynamic";
     pub const INTRINSIC_FIXED: &'static str = "fixed";
     pub const INTRINSIC_IDENTITY: &'static str = "identity";
+    /// Merges the input args elementwise, broadcasting the dims of 1.
+    pub const INTRINSIC_BROADCAST: &'static str = "broadcast";
+    /// Concatenates the input args along the `axis` variable.
+    pub const INTRINSIC_CONCAT: &'static str = "concat";
 
     const INTRINSIC_GENERIC: &'static str = "";
 }
@@ -26,6 +37,8 @@ impl Default for Node {
             graph: None,
             inputs: vec![],
             shapes: Shapes::Dynamic,
+            dtypes: BTreeMap::new(),
+            poisoned: false,
         }
     }
 }
@@ -6,8 +6,8 @@ use std::path::{Path, PathBuf};
 use super::graph::Graph;
 use crate::compile::Compile;
 use crate::error::{CompileError, ModelError};
+use crate::source::{DirSource, EmbeddedSource, ModelFile, ModelSource, RegistrySource, STD_DIR};
 
-use include_dir::{include_dir, Dir};
 use n3_parser::ast;
 use n3_parser::parser;
 
@@ -17,28 +17,78 @@ pub struct GraphRoot {
     graphs: HashMap<String, Graph>,
     compiling: HashSet<String>,
 
-    prefabs: HashMap<String, ast::File>,
+    prefabs: HashMap<String, Prefab>,
+    /// The sources of the models, where the earlier ones take priority.
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_sources"))]
+    sources: Vec<Box<dyn ModelSource>>,
+    /// The models which the earlier sources are allowed to shadow, or all of them if `None`.
+    shadowing: Option<HashSet<String>>,
+
+    users: HashMap<String, HashMap<String, Prefab>>,
+
+    sites: HashMap<String, HashMap<String, Prefab>>,
+
+    /// The sources of the compiled models, to compile them again once they are invalidated.
+    compiled: HashMap<String, Compiled>,
+    /// The files which have been changed since the last reload.
+    changed: HashSet<PathBuf>,
+    /// The files which fail to parse, reported only once their models are requested.
+    broken: Vec<Broken>,
+
+    /// The errors recovered so far, if recovering from errors.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    errors: Option<Vec<CompileError>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    poisoned: Vec<(String, ast::UseOrigin, String, Prefab)>,
+    /// The origins of the models being compiled, to find the siblings of the package models.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    namespaces: Vec<ast::UseOrigin>,
 }
 
 impl Default for GraphRoot {
     fn default() -> Self {
-        Self {
-            graphs: HashMap::default(),
-            compiling: HashSet::default(),
-
-            prefabs: Self::load_graph_prefabs_no_local().unwrap(),
-        }
+        Self::new(default_sources(), None).unwrap()
     }
 }
 
 impl GraphRoot {
+    /// Loads the local models under the path, with the std models.
+    ///
+    /// The files which fail to parse do not fail the others,
+    /// but are reported once their models are requested.
     pub fn with_path<P: AsRef<Path>>(pwd: P) -> Result<Self, CompileError> {
-        Ok(Self {
-            graphs: HashMap::default(),
-            compiling: HashSet::default(),
+        Self::with_path_shadowing_std(pwd, &[])
+    }
 
-            prefabs: Self::load_graph_prefabs(Some(pwd))?,
-        })
+    /// Loads the local models like `with_path`, but lets the given local models replace
+    /// the std models of the same names.
+    ///
+    /// Otherwise, the models of the same name are reported as `DuplicatedModel`.
+    pub fn with_path_shadowing_std<P: AsRef<Path>>(
+        pwd: P,
+        models: &[&str],
+    ) -> Result<Self, CompileError> {
+        let sources: Vec<Box<dyn ModelSource>> = vec![
+            Box::new(DirSource::new(pwd)),
+            Box::new(EmbeddedSource::std()),
+        ];
+        let shadowing = models.iter().map(|m| m.to_string()).collect();
+        Self::new(sources, Some(shadowing))
+    }
+
+    /// Loads the models from the sources, where the earlier sources shadow the models
+    /// of the same names in the later ones.
+    pub fn with_sources(sources: Vec<Box<dyn ModelSource>>) -> Result<Self, CompileError> {
+        Self::new(sources, None)
+    }
+
+    /// Adds the package registry used to resolve `use ... by "site"`.
+    ///
+    /// The registry is chained after the other sources as a [`RegistrySource`], so the site
+    /// packages given by the earlier sources, including the registries added before, take priority.
+    pub fn set_site_registry<P: AsRef<Path>>(&mut self, registry: P) {
+        self.sources.push(Box::new(RegistrySource::new(registry)));
+        self.sites.clear();
     }
 
     pub fn find_graph(
@@ -46,9 +96,10 @@ impl GraphRoot {
         name: &str,
         origin: ast::UseOrigin,
     ) -> Result<&Graph, CompileError> {
-        if self.graphs.contains_key(name) {
-            Ok(&self.graphs[name])
-        } else if self.compiling.contains(name) {
+        let key = graph_key(name, &origin);
+        if self.graphs.contains_key(&key) {
+            Ok(&self.graphs[&key])
+        } else if self.compiling.contains(&key) {
             recursive_model(name, origin)
         } else {
             self.load_graph(name, origin)
@@ -56,117 +107,670 @@ impl GraphRoot {
     }
 
     pub fn compile_from_source(&mut self, source: &str) -> Result<&Graph, CompileError> {
-        let (name, ast) = Self::load_graph_prefab(PathBuf::new(), source)?;
-        let graph = ast.compile(self)?;
-        self.graphs.insert(name.clone(), graph);
+        let name = self.compile_source(source)?;
         Ok(&self.graphs[&name])
     }
+
+    /// Compiles the model like `find_graph`, but keeps compiling the following nodes
+    /// and models after a failure, returning all the errors found.
+    pub fn check_graph(
+        &mut self,
+        name: &str,
+        origin: ast::UseOrigin,
+    ) -> Result<&Graph, Vec<CompileError>> {
+        let key = graph_key(name, &origin);
+        self.recover(|root| root.find_graph(name, origin).map(|_| ()))?;
+        Ok(&self.graphs[&key])
+    }
+
+    /// Compiles the source like `compile_from_source`, but keeps compiling the following nodes
+    /// and models after a failure, returning all the errors found.
+    pub fn check_from_source(&mut self, source: &str) -> Result<&Graph, Vec<CompileError>> {
+        let mut name = String::new();
+        self.recover(|root| {
+            name = root.compile_source(source)?;
+            Ok(())
+        })?;
+        Ok(&self.graphs[&name])
+    }
+
+    /// Marks the model file as changed, including the created and the removed ones.
+    ///
+    /// The file is read again on the next `reload`.
+    pub fn invalidate<P: AsRef<Path>>(&mut self, path: P) {
+        self.changed.insert(path.as_ref().to_path_buf());
+    }
+
+    /// Parses the changed files again, and drops the compiled models which are defined in them
+    /// or use them, directly or not.
+    ///
+    /// The dropped models are compiled again once they are found, and their keys are reported.
+    /// If a file fails to load, the others and the dependents are still reloaded,
+    /// and its error is reported with the keys.
+    pub fn reload(&mut self) -> ReloadReport {
+        let mut invalidated = HashSet::new();
+        let mut errors = vec![];
+        for path in std::mem::take(&mut self.changed) {
+            let mut origin = None;
+            for key in self.compiled_keys(|compiled| is_same_file(&compiled.path, &path)) {
+                let compiled = self.compiled.remove(&key).unwrap();
+                self.graphs.remove(&key);
+                invalidated.insert(key);
+                origin = Some(compiled.origin);
+            }
+            let origin = origin
+                .or_else(|| self.remove_prefab(&path))
+                .or_else(|| self.remove_broken(&path))
+                .or_else(|| self.find_origin(&path));
+
+            // the removed files have no models anymore
+            if let (Some(origin), Ok(source)) = (origin, fs::read_to_string(&path)) {
+                match self.load_changed_prefab(path.clone(), &source, &origin) {
+                    Ok((name, prefab)) => {
+                        self.prefabs_mut(&origin).insert(name, prefab);
+                    }
+                    Err(e) => {
+                        if let CompileError::ParseError { .. } = e.inner() {
+                            self.broken.push(Broken {
+                                origin,
+                                path,
+                                source,
+                            });
+                        }
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+
+        loop {
+            let dependents = self.compiled_keys(|compiled| {
+                compiled.uses.iter().any(|key| invalidated.contains(key))
+            });
+            if dependents.is_empty() {
+                break;
+            }
+
+            for key in dependents {
+                let compiled = self.compiled.remove(&key).unwrap();
+                self.graphs.remove(&key);
+                invalidated.insert(key);
+
+                let Compiled {
+                    origin,
+                    path,
+                    source,
+                    ..
+                } = compiled;
+                match Self::load_graph_prefab(path.clone(), &source) {
+                    Ok((name, prefab)) => {
+                        self.prefabs_mut(&origin).insert(name, prefab);
+                    }
+                    Err(e) => {
+                        if let CompileError::ParseError { .. } = e.inner() {
+                            self.broken.push(Broken {
+                                origin,
+                                path,
+                                source,
+                            });
+                        }
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+
+        let mut invalidated: Vec<_> = invalidated.into_iter().collect();
+        invalidated.sort();
+        ReloadReport {
+            invalidated,
+            errors,
+        }
+    }
+
+    /// Finds the model of the `use` statement.
+    ///
+    /// The local models used in a package are its siblings, or the std models.
+    pub(crate) fn find_use(
+        &mut self,
+        name: &str,
+        origin: ast::UseOrigin,
+    ) -> Result<&Graph, CompileError> {
+        let origin = match self.namespaces.last() {
+            Some(namespace) => self.use_origin(name, &origin, namespace),
+            None => origin,
+        };
+        self.find_graph(name, origin)
+    }
+
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.errors.is_some()
+    }
 }
 
 impl GraphRoot {
+    fn new(
+        sources: Vec<Box<dyn ModelSource>>,
+        shadowing: Option<HashSet<String>>,
+    ) -> Result<Self, CompileError> {
+        let mut broken = vec![];
+        let origin = ast::UseOrigin::Local;
+        let prefabs = Self::collect_origin(&sources, &origin, shadowing.as_ref(), &mut broken)?;
+
+        Ok(Self {
+            graphs: HashMap::default(),
+            compiling: HashSet::default(),
+
+            prefabs: prefabs.unwrap_or_default(),
+            sources,
+            shadowing,
+
+            users: HashMap::default(),
+
+            sites: HashMap::default(),
+
+            compiled: HashMap::default(),
+            changed: HashSet::default(),
+            broken,
+
+            errors: None,
+            poisoned: vec![],
+            namespaces: vec![],
+        })
+    }
+
     fn load_graph(&mut self, name: &str, origin: ast::UseOrigin) -> Result<&Graph, CompileError> {
-        if self.compiling.insert(name.to_string()) {
-            let model = match origin {
-                ast::UseOrigin::Site(site) => self.load_graph_site(name, site),
-                ast::UseOrigin::User(user) => self.load_graph_user(name, user),
-                ast::UseOrigin::Local => self.load_graph_local(name),
-            }?;
-            self.compiling.remove(name);
-            self.graphs.insert(name.to_string(), model);
-            Ok(&self.graphs[name])
+        let key = graph_key(name, &origin);
+        if self.compiling.insert(key.clone()) {
+            let model = self.take_prefab(name, &origin).and_then(|prefab| {
+                let (path, source) = (prefab.path.clone(), prefab.source.clone());
+                let uses = prefab
+                    .ast
+                    .uses
+                    .iter()
+                    .map(|u| graph_key(&u.model, &self.use_origin(&u.model, &u.origin, &origin)))
+                    .collect();
+
+                self.namespaces.push(clone_origin(&origin));
+                let model = self.compile_prefab(prefab);
+                self.namespaces.pop();
+                match model {
+                    Ok(model) => Ok((model, path, source, uses)),
+                    Err(error) => {
+                        // keep the model to compile it again, once the models it uses are fixed
+                        let (name, prefab) = Self::load_graph_prefab(path, &source)?;
+                        self.prefabs_mut(&origin).insert(name, prefab);
+                        Err(error)
+                    }
+                }
+            });
+            self.compiling.remove(&key);
+            let (model, path, source, uses) = model?;
+
+            // keep the broken graph until recovering is over, so that it is compiled only once
+            if model.is_poisoned() {
+                let (name, prefab) = Self::load_graph_prefab(path, &source)?;
+                self.poisoned.push((key.clone(), origin, name, prefab));
+            } else {
+                let compiled = Compiled {
+                    origin,
+                    path,
+                    source,
+                    uses,
+                };
+                self.compiled.insert(key.clone(), compiled);
+            }
+            self.graphs.insert(key.clone(), model);
+            Ok(&self.graphs[&key])
         } else {
             recursive_model(name, origin)
         }
     }
 
-    fn load_graph_site(&mut self, name: &str, site: String) -> Result<Graph, CompileError> {
-        unimplemented!()
+    fn compile_source(&mut self, source: &str) -> Result<String, CompileError> {
+        let (name, prefab) = Self::load_graph_prefab(PathBuf::new(), source)?;
+        let graph = self.compile_prefab(prefab)?;
+        if !graph.is_poisoned() {
+            self.graphs.insert(name.clone(), graph);
+        }
+        Ok(name)
     }
 
-    fn load_graph_user(&mut self, name: &str, site: String) -> Result<Graph, CompileError> {
-        unimplemented!()
-    }
+    fn recover<F>(&mut self, f: F) -> Result<(), Vec<CompileError>>
+    where
+        F: FnOnce(&mut Self) -> Result<(), CompileError>,
+    {
+        self.errors = Some(vec![]);
+        let result = f(self);
+        let mut errors = self.errors.take().unwrap_or_default();
+        errors.extend(result.err());
+
+        // the broken graphs should be compiled again next time
+        for (key, origin, name, prefab) in std::mem::take(&mut self.poisoned) {
+            self.graphs.remove(&key);
+            self.prefabs_mut(&origin).insert(name, prefab);
+        }
 
-    fn load_graph_local(&mut self, name: &str) -> Result<Graph, CompileError> {
-        if let Some(ast) = self.prefabs.remove(name) {
-            ast.compile(self)
+        if errors.is_empty() {
+            Ok(())
         } else {
-            model_not_found(name, ast::UseOrigin::Local)
+            Err(errors)
         }
     }
 
-    fn load_graph_prefabs<P: AsRef<Path>>(
-        pwd: Option<P>,
-    ) -> Result<HashMap<String, ast::File>, CompileError> {
-        match pwd {
-            Some(pwd) => Self::load_graph_prefabs_local(pwd),
-            None => Self::load_graph_prefabs_no_local(),
+    /// Parses the changed file, which should not declare the model of the other files.
+    fn load_changed_prefab(
+        &self,
+        path: PathBuf,
+        source: &str,
+        origin: &ast::UseOrigin,
+    ) -> Result<(String, Prefab), CompileError> {
+        let (name, prefab) = Self::load_graph_prefab(path, source)?;
+
+        let prefabs = match origin {
+            ast::UseOrigin::Site(site) => self.sites.get(site),
+            ast::UseOrigin::User(user) => self.users.get(user),
+            ast::UseOrigin::Local => Some(&self.prefabs),
+        };
+        let other = prefabs
+            .and_then(|prefabs| prefabs.get(&name))
+            .map(|other| &other.path)
+            .or_else(|| {
+                let key = graph_key(&name, origin);
+                self.compiled.get(&key).map(|other| &other.path)
+            });
+
+        match other {
+            Some(other) if !is_same_file(other, &prefab.path) => {
+                let shadows = match &self.shadowing {
+                    Some(shadowing) => is_std_file(other) && shadowing.contains(&name),
+                    None => true,
+                };
+                if shadows {
+                    Ok((name, prefab))
+                } else {
+                    Err(duplicated_model(name, other.clone(), prefab.path))
+                }
+            }
+            _ => Ok((name, prefab)),
         }
     }
 
-    fn load_graph_prefabs_no_local() -> Result<HashMap<String, ast::File>, CompileError> {
-        Self::load_graph_prefabs_embed()
-            .into_iter()
-            .map(|(path, source)| Self::load_graph_prefab(path, &source))
+    fn compiled_keys<F>(&self, f: F) -> Vec<String>
+    where
+        F: Fn(&Compiled) -> bool,
+    {
+        self.compiled
+            .iter()
+            .filter(|(_, compiled)| f(compiled))
+            .map(|(key, _)| key.clone())
             .collect()
     }
 
-    fn load_graph_prefabs_embed() -> Vec<(PathBuf, String)> {
-        STD_DIR
-            .find("**/*.n3")
-            .unwrap()
-            .filter_map(|r| STD_DIR.get_file(r.path()))
-            .filter_map(|p| p.contents_utf8().map(|s| (p.path().into(), s.to_string())))
-            .collect()
+    /// Removes the prefab which is not compiled yet, and returns its origin.
+    fn remove_prefab(&mut self, path: &Path) -> Option<ast::UseOrigin> {
+        let remove = |prefabs: &mut HashMap<String, Prefab>| {
+            let name = prefabs
+                .iter()
+                .find(|(_, prefab)| is_same_file(&prefab.path, path))
+                .map(|(name, _)| name.clone())?;
+            prefabs.remove(&name)
+        };
+
+        if remove(&mut self.prefabs).is_some() {
+            return Some(ast::UseOrigin::Local);
+        }
+        for (user, prefabs) in &mut self.users {
+            if remove(prefabs).is_some() {
+                return Some(ast::UseOrigin::User(user.clone()));
+            }
+        }
+        for (site, prefabs) in &mut self.sites {
+            if remove(prefabs).is_some() {
+                return Some(ast::UseOrigin::Site(site.clone()));
+            }
+        }
+        None
+    }
+
+    /// Resolves the origin of the model used in the namespace.
+    fn use_origin(
+        &self,
+        name: &str,
+        origin: &ast::UseOrigin,
+        namespace: &ast::UseOrigin,
+    ) -> ast::UseOrigin {
+        match (origin, namespace) {
+            (ast::UseOrigin::Local, ast::UseOrigin::Local) => ast::UseOrigin::Local,
+            (ast::UseOrigin::Local, _) if !self.is_std_model(name) => clone_origin(namespace),
+            (origin, _) => clone_origin(origin),
+        }
     }
 
-    fn load_graph_prefab(path: PathBuf, source: &str) -> Result<(String, ast::File), CompileError> {
-        let ast = parser::parse_file(source)
-            .or_else(|e| Err(CompileError::ParseError { error: e, path }))?;
+    fn is_std_model(&self, name: &str) -> bool {
+        self.prefabs
+            .get(name)
+            .map(|prefab| &prefab.path)
+            .or_else(|| self.compiled.get(name).map(|compiled| &compiled.path))
+            .map(|path| is_std_file(path))
+            .unwrap_or_default()
+    }
+
+    /// Forgets the file which has failed to parse, and returns its origin.
+    fn remove_broken(&mut self, path: &Path) -> Option<ast::UseOrigin> {
+        let index = self
+            .broken
+            .iter()
+            .position(|broken| is_same_file(&broken.path, path))?;
+        Some(self.broken.remove(index).origin)
+    }
+
+    /// Reports the parse error of the file which may declare the model.
+    ///
+    /// The file of the same model name is preferred, and then the ones whose names are unknown.
+    fn find_broken(&self, name: &str, origin: &ast::UseOrigin) -> Option<CompileError> {
+        let key = graph_key(name, origin);
+        let candidates: Vec<_> = self
+            .broken
+            .iter()
+            .filter(|broken| graph_key(name, &broken.origin) == key)
+            .map(|broken| (declared_model(&broken.source), broken))
+            .collect();
+
+        let broken = candidates
+            .iter()
+            .find(|(model, _)| model.as_deref() == Some(name))
+            .or_else(|| candidates.iter().find(|(model, _)| model.is_none()))?
+            .1;
+        Self::load_graph_prefab(broken.path.clone(), &broken.source).err()
+    }
+
+    /// Finds the origin of the new file, if its namespace has been loaded.
+    fn find_origin(&self, path: &Path) -> Option<ast::UseOrigin> {
+        match self
+            .sources
+            .iter()
+            .find_map(|source| source.origin_of(path))?
+        {
+            ast::UseOrigin::User(user) if !self.users.contains_key(&user) => None,
+            origin => Some(origin),
+        }
+    }
+
+    fn take_prefab(&mut self, name: &str, origin: &ast::UseOrigin) -> Result<Prefab, CompileError> {
+        let result = match origin {
+            ast::UseOrigin::Site(site) => self.take_prefab_site(name, site),
+            ast::UseOrigin::User(user) => self.take_prefab_user(name, user),
+            ast::UseOrigin::Local => self.take_prefab_local(name),
+        };
+
+        // the model may be declared in the file which fails to parse
+        match result {
+            Err(CompileError::ModelError {
+                error: ModelError::ModelNotFound,
+                ..
+            }) => match self.find_broken(name, origin) {
+                Some(error) => Err(error),
+                None => result,
+            },
+            result => result,
+        }
+    }
+
+    fn prefabs_mut(&mut self, origin: &ast::UseOrigin) -> &mut HashMap<String, Prefab> {
+        match origin {
+            ast::UseOrigin::Site(site) => self.sites.entry(site.clone()).or_default(),
+            ast::UseOrigin::User(user) => self.users.entry(user.clone()).or_default(),
+            ast::UseOrigin::Local => &mut self.prefabs,
+        }
+    }
+
+    fn take_prefab_site(&mut self, name: &str, site: &str) -> Result<Prefab, CompileError> {
+        if !self.sites.contains_key(site) {
+            let origin = ast::UseOrigin::Site(site.to_string());
+            let shadowing = self.shadowing.as_ref();
+            let prefabs =
+                match Self::collect_origin(&self.sources, &origin, shadowing, &mut self.broken)? {
+                    Some(prefabs) => prefabs,
+                    None => {
+                        return Err(CompileError::ModelError {
+                            error: ModelError::SiteNotFound,
+                            model: name.to_string(),
+                            origin,
+                        })
+                    }
+                };
+            self.sites.insert(site.to_string(), prefabs);
+        }
+
+        match self.sites.get_mut(site).and_then(|s| s.remove(name)) {
+            Some(prefab) => Ok(prefab),
+            None => model_not_found(name, ast::UseOrigin::Site(site.to_string())),
+        }
+    }
+
+    fn take_prefab_user(&mut self, name: &str, user: &str) -> Result<Prefab, CompileError> {
+        if !self.users.contains_key(user) {
+            let origin = ast::UseOrigin::User(user.to_string());
+            let shadowing = self.shadowing.as_ref();
+            let prefabs =
+                match Self::collect_origin(&self.sources, &origin, shadowing, &mut self.broken)? {
+                    Some(prefabs) => prefabs,
+                    None => {
+                        return Err(CompileError::ModelError {
+                            error: ModelError::UserNotFound,
+                            model: name.to_string(),
+                            origin,
+                        })
+                    }
+                };
+            self.users.insert(user.to_string(), prefabs);
+        }
+
+        match self.users.get_mut(user).and_then(|u| u.remove(name)) {
+            Some(prefab) => Ok(prefab),
+            None => model_not_found(name, ast::UseOrigin::User(user.to_string())),
+        }
+    }
+
+    fn take_prefab_local(&mut self, name: &str) -> Result<Prefab, CompileError> {
+        if let Some(prefab) = self.prefabs.remove(name) {
+            Ok(prefab)
+        } else {
+            model_not_found(name, ast::UseOrigin::Local)
+        }
+    }
+
+    /// Parses the models of the origin from the sources, where the earlier sources take priority.
+    ///
+    /// Returns `None` if none of the sources has the origin.
+    fn collect_origin(
+        sources: &[Box<dyn ModelSource>],
+        origin: &ast::UseOrigin,
+        shadowing: Option<&HashSet<String>>,
+        broken: &mut Vec<Broken>,
+    ) -> Result<Option<HashMap<String, Prefab>>, CompileError> {
+        let mut collected: Option<HashMap<String, Prefab>> = None;
+        for source in sources {
+            let files = match source.files(origin) {
+                Some(files) => files,
+                None => continue,
+            };
+            let prefabs = Self::collect_prefabs(files, clone_origin(origin), broken)?;
+
+            let collected = collected.get_or_insert_with(HashMap::new);
+            for (name, prefab) in prefabs {
+                match collected.get(&name) {
+                    Some(_) if shadowing.map(|s| s.contains(&name)).unwrap_or(true) => {}
+                    Some(other) => {
+                        return Err(duplicated_model(name, other.path.clone(), prefab.path))
+                    }
+                    None => {
+                        collected.insert(name, prefab);
+                    }
+                }
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Parses the sources, which should declare the models of different names.
+    ///
+    /// The files which fail to parse are put aside into `broken`.
+    fn collect_prefabs(
+        sources: Vec<ModelFile>,
+        origin: ast::UseOrigin,
+        broken: &mut Vec<Broken>,
+    ) -> Result<HashMap<String, Prefab>, CompileError> {
+        let mut prefabs: HashMap<String, Prefab> = HashMap::new();
+        for ModelFile { path, source } in sources {
+            let (name, prefab) = match Self::load_graph_prefab(path.clone(), &source) {
+                Ok(loaded) => loaded,
+                Err(_) => {
+                    broken.push(Broken {
+                        origin: clone_origin(&origin),
+                        path,
+                        source,
+                    });
+                    continue;
+                }
+            };
+            if let Some(other) = prefabs.get(&name) {
+                return Err(duplicated_model(name, other.path.clone(), prefab.path));
+            }
+            prefabs.insert(name, prefab);
+        }
+        Ok(prefabs)
+    }
 
+    fn load_graph_prefab(path: PathBuf, source: &str) -> Result<(String, Prefab), CompileError> {
+        let ast = match parser::parse_file(source) {
+            Ok(ast) => ast,
+            Err(error) => {
+                let error = CompileError::ParseError {
+                    error,
+                    path: path.clone(),
+                };
+                return Err(error.with_source(&path, source));
+            }
+        };
+
+        let name = ast.model.name.clone();
+        let prefab = Prefab {
+            path,
+            source: source.to_string(),
+            ast,
+        };
+
+        Ok((name, prefab))
+    }
+
+    fn compile_prefab(&mut self, prefab: Prefab) -> Result<Graph, CompileError> {
+        let Prefab { path, source, ast } = prefab;
         let name = ast.model.name.clone();
+        let mut graph = ast
+            .compile(self)
+            .map_err(|error| error.with_source(&path, &source))?;
+        if is_std_file(&path) {
+            graph.set_std_model(name);
+        }
 
-        Ok((name, ast))
+        let errors = graph.take_errors();
+        if let Some(recovered) = &mut self.errors {
+            recovered.extend(errors.into_iter().map(|e| e.with_source(&path, &source)));
+        }
+        Ok(graph)
     }
 }
 
-static STD_DIR: Dir<'static> = include_dir!("std");
+/// The models dropped by [`GraphRoot::reload`], with the errors of the files failed to load.
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// The keys of the dropped models, in order.
+    pub invalidated: Vec<String>,
+    /// The errors of the changed files, such as the parse errors.
+    pub errors: Vec<CompileError>,
+}
 
-#[cfg(not(target_arch = "wasm32"))]
-impl GraphRoot {
-    fn load_graph_prefabs_local<P: AsRef<Path>>(
-        pwd: P,
-    ) -> Result<HashMap<String, ast::File>, CompileError> {
-        walkdir::WalkDir::new(pwd)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|r| !r.metadata().map(|m| m.is_dir()).unwrap_or(true))
-            .map(|r| r.path().into())
-            .filter_map(|p: PathBuf| {
-                let source = fs::read_to_string(&p).ok()?;
-                if p.to_str().unwrap().ends_with(".n3") {
-                    Some((p, source))
-                } else {
-                    None
-                }
-            })
-            .chain(Self::load_graph_prefabs_embed())
-            .map(|(p, s)| Self::load_graph_prefab(p, &s))
-            .collect()
+/// A parsed model file, kept with its source to locate compile errors.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+struct Prefab {
+    path: PathBuf,
+    source: String,
+    ast: ast::File,
+}
+
+/// A model file which fails to parse.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+struct Broken {
+    origin: ast::UseOrigin,
+    path: PathBuf,
+    source: String,
+}
+
+/// The source of a compiled model, with the keys of the models it uses.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug)]
+struct Compiled {
+    origin: ast::UseOrigin,
+    path: PathBuf,
+    source: String,
+    uses: Vec<String>,
+}
+
+fn graph_key(name: &str, origin: &ast::UseOrigin) -> String {
+    match origin {
+        ast::UseOrigin::Site(site) => format!("{} by \"{}\"", name, site),
+        ast::UseOrigin::User(user) => format!("{} by {}", name, user),
+        ast::UseOrigin::Local => name.to_string(),
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-impl GraphRoot {
-    fn load_graph_prefabs_local<P: AsRef<Path>>(
-        pwd: P,
-    ) -> Result<HashMap<String, ast::File>, CompileError> {
-        println!(
-            "Initializing GraphRoot with path on wasm is not supported yet: {}",
-            pwd.as_ref().display()
-        );
-        Self::load_graph_prefabs_no_local()
+fn default_sources() -> Vec<Box<dyn ModelSource>> {
+    vec![Box::new(EmbeddedSource::std())]
+}
+
+fn clone_origin(origin: &ast::UseOrigin) -> ast::UseOrigin {
+    match origin {
+        ast::UseOrigin::Site(site) => ast::UseOrigin::Site(site.clone()),
+        ast::UseOrigin::User(user) => ast::UseOrigin::User(user.clone()),
+        ast::UseOrigin::Local => ast::UseOrigin::Local,
+    }
+}
+
+/// Guesses the name of the model which the source declares, even if it fails to parse.
+fn declared_model(source: &str) -> Option<String> {
+    source.lines().find_map(|line| {
+        let line = line.strip_prefix("extern ").unwrap_or(line);
+        let name = line.strip_prefix('[')?;
+        let name = name.split(']').next().unwrap_or(name).trim();
+        Some(name.to_string()).filter(|name| !name.is_empty())
+    })
+}
+
+/// Tests whether the file is one of the embedded std models.
+fn is_std_file(path: &Path) -> bool {
+    path.is_relative() && STD_DIR.get_file(path).is_some()
+}
+
+/// Tests whether the paths point the same file, even if one of them is relative.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
+
+/// Reports the models of the same name, in the order of their paths.
+fn duplicated_model(model: String, a: PathBuf, b: PathBuf) -> CompileError {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    CompileError::DuplicatedModel {
+        model,
+        first,
+        second,
     }
 }
 
@@ -1,11 +1,11 @@
 use std::collections::BTreeMap;
+use std::fmt::{self, Write};
 use std::ops;
 
 use super::id::GraphId;
 use crate::error::GraphError;
 
 use generator::{Generator, Gn};
-use heck::CamelCase;
 use symengine::{Expression, ExpressionMapKey};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -43,6 +43,43 @@ impl ShapeState {
     }
 }
 
+/// The type of the elements of a tensor.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DType {
+    F32,
+    F16,
+    I64,
+    Bool,
+}
+
+impl DType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "f32" => Some(Self::F32),
+            "f16" => Some(Self::F16),
+            "i64" => Some(Self::I64),
+            "bool" => Some(Self::Bool),
+            _ => None,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::F32 | Self::F16)
+    }
+}
+
+impl fmt::Display for DType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::F32 => write!(f, "f32"),
+            Self::F16 => write!(f, "f16"),
+            Self::I64 => write!(f, "i64"),
+            Self::Bool => write!(f, "bool"),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
 pub enum Shapes {
@@ -90,6 +127,17 @@ impl Shapes {
         }
     }
 
+    /// Returns the batch axis of the first arg, or [`Dim::batch`] if not fixed yet.
+    pub fn batch(&self) -> Dim {
+        match self {
+            Self::Fixed(shapes) => match shapes.values().next() {
+                Some(shape @ Shape::Fixed(_)) => shape.unwrap_batch().clone(),
+                _ => Dim::batch(),
+            },
+            Self::Dynamic => Dim::batch(),
+        }
+    }
+
     pub fn unwrap_shapes(&self) -> &BTreeMap<u64, Shape> {
         match self {
             Self::Fixed(shapes) => shapes,
@@ -147,31 +195,40 @@ impl Shapes {
 #[derive(Clone, Debug)]
 pub enum Shape {
     Dynamic,
+    /// The dims of the tensor, which begin with the batch axis.
     Fixed(Vec<Dim>),
 }
 
 impl Shape {
+    /// Prepends the batch axis to the declared dims.
+    pub fn with_batch(batch: Dim, dims: Vec<Dim>) -> Self {
+        Self::Fixed(Some(batch).into_iter().chain(dims).collect())
+    }
+
+    /// Flattens the dims into one, keeping the batch axis.
     pub fn product(self) -> Self {
         match self {
             Self::Dynamic => self,
             Self::Fixed(dims) => {
-                let product = dims
+                let batch = dims[0].clone();
+                let product = dims[1..]
                     .iter()
                     .map(|dim| match dim {
                         Dim::Key(key) => Expression::new(key.to_string()),
                         Dim::Expr(expr) => expr.clone(),
+                        Dim::Floor { .. } => dim.to_expr(),
                     })
                     .fold(1.0.into(), ops::Mul::mul);
-                Self::Fixed(vec![Dim::Expr(product)])
+                Self::with_batch(batch, vec![Dim::Expr(product)])
             }
         }
     }
 
     pub fn validate_rank(&self, last: &Self, id: &GraphId, arg: &u64) -> Result<bool, GraphError> {
         match (self, last) {
-            (Self::Fixed(dims), Self::Fixed(last_dims)) => {
-                let rank = dims.len();
-                let last_rank = last_dims.len();
+            (Self::Fixed(_), Self::Fixed(_)) => {
+                let rank = self.unwrap_dims().len();
+                let last_rank = last.unwrap_dims().len();
                 if rank == last_rank {
                     Ok(true)
                 } else {
@@ -187,12 +244,21 @@ impl Shape {
         }
     }
 
+    /// Returns the declared dims, without the batch axis.
     pub fn unwrap_dims(&self) -> &[Dim] {
+        &self.unwrap_dims_with_batch()[1..]
+    }
+
+    pub fn unwrap_dims_with_batch(&self) -> &[Dim] {
         match self {
             Self::Fixed(dims) => dims,
             _ => unreachable!(),
         }
     }
+
+    pub fn unwrap_batch(&self) -> &Dim {
+        &self.unwrap_dims_with_batch()[0]
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -200,13 +266,67 @@ impl Shape {
 pub enum Dim {
     Key(DimKey),
     Expr(Expression),
+    /// A quotient rounded down, plus the exact offset, such as `floor((H - 1)/2) + 1`.
+    ///
+    /// This is kept apart from [`Dim::Expr`], where `floor(..)` would be an opaque function:
+    /// the compiler proves the declared dims of the extern models against the floors through
+    /// their quotients and offsets, see [`Dim::floor_div`].
+    Floor {
+        quotient: Expression,
+        offset: Expression,
+    },
 }
 
 impl Dim {
+    /// The batch axis, which is implicitly prepended to every tensor.
+    pub fn batch() -> Self {
+        Self::Key(DimKey::Batch)
+    }
+
+    pub fn is_batch(&self) -> bool {
+        matches!(self, Self::Key(DimKey::Batch))
+    }
+
     pub fn to_expr(&self) -> Expression {
         match self {
             Self::Key(key) => key.to_expr(),
             Self::Expr(expr) => expr.clone(),
+            Self::Floor { quotient, offset } => floor(quotient) + offset.clone(),
+        }
+    }
+
+    /// Divides the dimension, rounding the quotient down, as the extern models do.
+    ///
+    /// The divisor is tested by the graph, with the values of its variables.
+    pub fn floor_div(self, rhs: Self) -> Self {
+        Self::new_floor((self / rhs).to_expr(), 0u64.into())
+    }
+
+    /// Rounds the quotient down, at once if it is a number.
+    pub(crate) fn new_floor(quotient: Expression, offset: Expression) -> Self {
+        if is_number(&quotient) {
+            Self::Expr(floor(&quotient) + offset)
+        } else {
+            Self::Floor { quotient, offset }
+        }
+    }
+}
+
+impl fmt::Display for Dim {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Key(DimKey::Variable(name)) | Self::Key(DimKey::Placeholder(name, _)) => {
+                write!(f, "{}", name)
+            }
+            Self::Key(DimKey::Batch) => write!(f, "{}", BATCH),
+            Self::Expr(expr) => write!(f, "{}", decode_symbols(expr.as_str())),
+            Self::Floor { quotient, offset } => {
+                write!(f, "floor({})", decode_symbols(quotient.as_str()))?;
+                if *offset != 0u64 {
+                    write!(f, " + {}", decode_symbols(offset.as_str()))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -215,7 +335,18 @@ impl ops::Add for Dim {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self {
-        Dim::Expr(self.to_expr() + rhs.to_expr())
+        match (self, rhs) {
+            (lhs @ Dim::Floor { .. }, rhs @ Dim::Floor { .. }) => {
+                Dim::Expr(lhs.to_expr() + rhs.to_expr())
+            }
+            (Dim::Floor { quotient, offset }, other) | (other, Dim::Floor { quotient, offset }) => {
+                Dim::Floor {
+                    quotient,
+                    offset: offset + other.to_expr(),
+                }
+            }
+            (lhs, rhs) => Dim::Expr(lhs.to_expr() + rhs.to_expr()),
+        }
     }
 }
 
@@ -223,7 +354,14 @@ impl ops::Sub for Dim {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self {
-        Dim::Expr(self.to_expr() - rhs.to_expr())
+        match (self, rhs) {
+            (Dim::Floor { quotient, offset }, rhs @ Dim::Key(_))
+            | (Dim::Floor { quotient, offset }, rhs @ Dim::Expr(_)) => Dim::Floor {
+                quotient,
+                offset: offset - rhs.to_expr(),
+            },
+            (lhs, rhs) => Dim::Expr(lhs.to_expr() - rhs.to_expr()),
+        }
     }
 }
 
@@ -243,18 +381,99 @@ impl ops::Div for Dim {
     }
 }
 
+/// Rounds the expression down, which is evaluated once it becomes a number.
+pub(crate) fn floor(expr: &Expression) -> Expression {
+    Expression::new(format!("floor({})", expr.as_str()))
+}
+
+/// Returns whether the expression has no symbols.
+pub(crate) fn is_number(expr: &Expression) -> bool {
+    !expr
+        .as_str()
+        .contains(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// Serialized as a string such as `variable:N` in human-readable formats, to be the key of maps.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self"))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum DimKey {
     Variable(String),
     Placeholder(String, bool),
+    /// The batch axis, which is not declared in the shapes.
+    Batch,
+}
+
+/// The name of the batch axis.
+const BATCH: &str = "batch";
+
+const VARIABLE_PREFIX: &str = "var_";
+const PLACEHOLDER_PREFIX: &str = "ph_";
+
+/// Encodes the name into a symbol, which has only the ASCII letters, digits and underscores.
+///
+/// The spaces are written as `__`, and the other characters as their code points like `_2d_`.
+fn encode_symbol(prefix: &str, name: &str) -> String {
+    let mut symbol = prefix.to_string();
+    for c in name.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => symbol.push(c),
+            ' ' => symbol.push_str("__"),
+            c => write!(symbol, "_{:x}_", c as u32).unwrap(),
+        }
+    }
+    symbol
+}
+
+fn decode_symbol(symbol: &str) -> Option<String> {
+    let encoded = symbol
+        .strip_prefix(VARIABLE_PREFIX)
+        .or_else(|| symbol.strip_prefix(PLACEHOLDER_PREFIX))?;
+
+    let mut name = String::new();
+    let mut chars = encoded.chars();
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            name.push(c);
+            continue;
+        }
+        let code: String = chars.by_ref().take_while(|&c| c != '_').collect();
+        if code.is_empty() {
+            name.push(' ');
+        } else {
+            name.push(std::char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+        }
+    }
+    Some(name)
+}
+
+/// Replaces the symbols in the expression with the names of the variables and the placeholders.
+fn decode_symbols(expr: &str) -> String {
+    let mut decoded = String::new();
+    let mut token = String::new();
+    for c in expr.chars().chain(std::iter::once('\0')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            token.push(c);
+            continue;
+        }
+        match decode_symbol(&token) {
+            Some(name) => decoded.push_str(&name),
+            None => decoded.push_str(&token),
+        }
+        token.clear();
+        if c != '\0' {
+            decoded.push(c);
+        }
+    }
+    decoded
 }
 
 impl ExpressionMapKey for DimKey {
     fn to_string(&self) -> String {
         match self {
-            Self::Variable(var) => format!("var_{}", var.to_camel_case()),
-            Self::Placeholder(ph, _) => format!("ph_{}", ph.to_camel_case()),
+            Self::Variable(var) => encode_symbol(VARIABLE_PREFIX, var),
+            Self::Placeholder(ph, _) => encode_symbol(PLACEHOLDER_PREFIX, ph),
+            Self::Batch => BATCH.to_string(),
         }
     }
 }
@@ -264,6 +483,7 @@ impl DimKey {
         match self {
             Self::Variable(var) => var,
             Self::Placeholder(ph, _) => ph,
+            Self::Batch => BATCH.to_string(),
         }
     }
 
@@ -271,3 +491,46 @@ impl DimKey {
         Expression::new(self.to_string())
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DimKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            let key = match self {
+                Self::Variable(var) => format!("variable:{}", var),
+                Self::Placeholder(ph, true) => format!("input:{}", ph),
+                Self::Placeholder(ph, false) => format!("placeholder:{}", ph),
+                Self::Batch => BATCH.to_string(),
+            };
+            serializer.serialize_str(&key)
+        } else {
+            Self::serialize(self, serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DimKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let key = <String as serde::Deserialize>::deserialize(deserializer)?;
+            if key == BATCH {
+                return Ok(Self::Batch);
+            }
+            match key.split_at(key.find(':').unwrap_or(0)) {
+                ("variable", name) => Ok(Self::Variable(name[1..].to_string())),
+                ("input", name) => Ok(Self::Placeholder(name[1..].to_string(), true)),
+                ("placeholder", name) => Ok(Self::Placeholder(name[1..].to_string(), false)),
+                _ => Err(serde::de::Error::custom(format!("invalid key: {}", key))),
+            }
+        } else {
+            Self::deserialize(deserializer)
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::error::GraphError;
 
 pub use n3_parser::ast::Value;
@@ -12,6 +14,13 @@ pub struct Variable {
 
 impl Variable {
     pub fn update(&mut self, value: Value, ty: ValueType) -> Result<(), GraphError> {
+        // the signed variables take the unsigned values as well
+        let (value, ty) = match (&self.ty, value) {
+            (ValueType::Int, Value::UInt(value)) if value <= i64::MAX as u64 => {
+                (Value::Int(value as i64), ValueType::Int)
+            }
+            (_, value) => (value, ty),
+        };
         if self.ty == ty || self.ty == ValueType::Required {
             self.value = Some(value);
             self.ty = ty;
@@ -74,3 +83,26 @@ impl ValueType {
         }
     }
 }
+
+/// Restores the sign of the negative integer, which the parser drops.
+///
+/// The parser reads only the negative literals such as `-1` as `Int`, but without their signs.
+pub(crate) fn restore_sign(value: Value) -> Value {
+    match value {
+        Value::Int(value) if value > 0 => Value::Int(-value),
+        value => value,
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Required => write!(f, "required"),
+            Self::Bool => write!(f, "bool"),
+            Self::Int => write!(f, "int"),
+            Self::UInt => write!(f, "uint"),
+            Self::Real => write!(f, "real"),
+            Self::Model => write!(f, "model"),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! A stable, versioned exchange format of the compiled graphs.
+//!
+//! The compiled [`Graph`](crate::Graph) follows the internals of the compiler, so its serialized
+//! form may change between releases. [`IrModule`] flattens it into plain tables instead, which
+//! refer to each other by their indices, so that the tools in other languages can consume it.
+//!
+//! All the tables are ordered deterministically: the models in the order of their first
+//! appearance from the root, and the nodes, edges and shapes in the order of the node ids.
+
+use std::collections::BTreeMap;
+
+use crate::codegen::{dim_name, input_placeholders};
+use crate::error::IrError;
+use crate::graphs::{DType, Graph, Value, Variable};
+
+/// The version of the schema, which is bumped on every incompatible change.
+pub const IR_VERSION: u32 = 1;
+
+/// A compiled graph, flattened into the tables.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrModule {
+    /// The version of the schema, which should be [`IR_VERSION`].
+    pub version: u32,
+    /// The models, where the first one is the root model.
+    pub models: Vec<IrModel>,
+    /// The nodes of all the non-extern models.
+    pub nodes: Vec<IrNode>,
+    /// The data flows between the nodes of the same model.
+    pub edges: Vec<IrEdge>,
+    /// The output shapes of the nodes.
+    pub shapes: Vec<IrShape>,
+}
+
+/// A model instance, which has its own variables resolved.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrModel {
+    /// The index in [`IrModule::models`].
+    pub id: usize,
+    pub name: String,
+    /// Whether the model is implemented outside of n3, so that it has no nodes.
+    pub is_extern: bool,
+    /// The variables, sorted by their names.
+    pub variables: Vec<IrVariable>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrVariable {
+    pub name: String,
+    /// The type of the value, such as `uint` or `model`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub value: Option<IrValue>,
+}
+
+/// A value of the variable, which is written with its type, such as `{"uint": 2}`.
+///
+/// The type is kept so that the values such as `Int(1)` and `UInt(1)` are read back as they were.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IrValue {
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    Real(f64),
+    Model(String),
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrNode {
+    /// The index in [`IrModule::nodes`].
+    pub id: usize,
+    /// The index of the model which has the node.
+    pub model: usize,
+    /// The id of the node in the model, such as `#1.1.0`.
+    pub graph_id: String,
+    pub name: String,
+    /// The index of the model which the node calls, if any.
+    pub graph: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrEdge {
+    /// The index of the node which gives the data.
+    pub from: usize,
+    /// The index of the node which takes the data.
+    pub to: usize,
+    /// The argument of the output, or all of them if not given.
+    pub arg: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IrShape {
+    /// The index of the node.
+    pub node: usize,
+    /// The argument of the output.
+    pub arg: usize,
+    /// The dimensions, where the symbolic ones are written as the expressions of
+    /// the input placeholders, such as `H` or `(1/2)*H`.
+    pub dims: Vec<String>,
+    /// The type of the elements, such as `f32`, if known.
+    pub dtype: Option<String>,
+}
+
+impl IrModule {
+    /// Flattens the compiled graph of the model.
+    pub fn new(name: &str, graph: &Graph) -> Self {
+        let mut module = Self {
+            version: IR_VERSION,
+            models: vec![],
+            nodes: vec![],
+            edges: vec![],
+            shapes: vec![],
+        };
+        module.add_model(name, graph, &[]);
+        module
+    }
+
+    /// Reads the module from JSON, checking its version first.
+    pub fn from_json(json: &str) -> Result<Self, IrError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|error| IrError::JsonError { error })?;
+
+        let version = value.get("version").and_then(|v| v.as_u64());
+        if version != Some(IR_VERSION as u64) {
+            return Err(IrError::UnsupportedVersion { version });
+        }
+        serde_json::from_value(value).map_err(|error| IrError::JsonError { error })
+    }
+
+    /// Writes the module in pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    fn add_model(&mut self, name: &str, graph: &Graph, symbols: &[(String, String)]) -> usize {
+        let model = self.models.len();
+
+        let mut variables: Vec<_> = graph
+            .get_variables()
+            .values()
+            .map(IrVariable::new)
+            .collect();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.models.push(IrModel {
+            id: model,
+            name: name.to_string(),
+            is_extern: graph.is_extern(),
+            variables,
+        });
+        if graph.is_extern() {
+            return model;
+        }
+
+        // the placeholders of the child graphs may be given from the parents
+        let symbols = [symbols, &input_placeholders(graph)].concat();
+
+        let mut indices = BTreeMap::new();
+        for (id, node) in graph.get_nodes() {
+            indices.insert(*id, self.nodes.len());
+            self.nodes.push(IrNode {
+                id: self.nodes.len(),
+                model,
+                graph_id: id.to_string(),
+                name: node.name.clone(),
+                graph: None,
+            });
+        }
+
+        for (id, node) in graph.get_nodes() {
+            for input in &node.inputs {
+                self.edges.push(IrEdge {
+                    from: indices[&input.id],
+                    to: indices[id],
+                    arg: input.arg,
+                });
+            }
+        }
+
+        let dtypes = graph.get_dtypes();
+        for (id, shapes) in graph.get_resolved_shapes(false) {
+            for (arg, dims) in shapes.iter().enumerate() {
+                self.shapes.push(IrShape {
+                    node: indices[&id],
+                    arg,
+                    dims: dims.iter().map(|dim| dim_name(&symbols, id, dim)).collect(),
+                    dtype: dtypes[&id].get(&(arg as u64)).map(DType::to_string),
+                });
+            }
+        }
+
+        for (id, node) in graph.get_nodes() {
+            if let Some(child) = &node.graph {
+                let child = self.add_model(&node.name, child, &symbols);
+                self.nodes[indices[id]].graph = Some(child);
+            }
+        }
+        model
+    }
+}
+
+impl IrVariable {
+    fn new(variable: &Variable) -> Self {
+        Self {
+            name: variable.description.clone(),
+            ty: variable.ty.to_string(),
+            value: variable.value.as_ref().map(|value| match value {
+                Value::Bool(value) => IrValue::Bool(*value),
+                Value::UInt(value) => IrValue::UInt(*value),
+                Value::Int(value) => IrValue::Int(*value),
+                Value::Real(value) => IrValue::Real(*value),
+                Value::Model(value) => IrValue::Model(value.clone()),
+            }),
+        }
+    }
+}
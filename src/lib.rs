@@ -1,14 +1,29 @@
 #[macro_use]
 extern crate generator;
 
+pub mod analysis;
+pub mod codegen;
 mod compile;
 mod error;
 mod graphs;
+#[cfg(feature = "ir")]
+pub mod ir;
+pub mod source;
+mod warning;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
 
-pub use self::error::CompileError;
+#[cfg(feature = "ir")]
+pub use self::error::IrError;
+pub use self::error::{
+    CodegenError, CompileError, ExternModelError, GraphError, ModelError, NonExternModelError, Span,
+};
 pub use self::graphs::{
-    Dim, DimKey, Graph, GraphId, GraphIdArg, GraphRoot, Node, Value, ValueType, Variable,
+    DType, Dim, DimKey, Graph, GraphId, GraphIdArg, GraphRoot, Node, ReloadReport, Value,
+    ValueType, Variable,
 };
 
+pub use self::warning::CompileWarning;
+
 pub use n3_parser::ast::UseOrigin;
 pub use symengine::Expression;
@@ -0,0 +1,430 @@
+//! The sources of the model files, from which a [`GraphRoot`](crate::GraphRoot) finds the models.
+//!
+//! A root may chain several sources, where the earlier ones take priority.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use include_dir::{include_dir, Dir};
+use n3_parser::ast::UseOrigin;
+use n3_parser::parser;
+
+pub(crate) static STD_DIR: Dir<'static> = include_dir!("std");
+
+/// The directory under the root path holding per-user namespaces.
+pub(crate) const USERS_DIR: &str = "users";
+/// The directory under the root of an archive holding the site packages.
+pub(crate) const SITES_DIR: &str = "sites";
+
+/// A model file, with the path used to report its errors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelFile {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// A place to find the model files, such as a directory or an archive.
+///
+/// A source may hold the local models, the user namespaces and the site packages,
+/// and returns `None` for the origins it does not have.
+pub trait ModelSource: fmt::Debug {
+    /// Reads all the model files of the origin, or `None` if the source does not have the origin.
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>>;
+
+    /// Lists the names of the models of the origin, skipping the files which fail to parse.
+    fn list(&self, origin: &UseOrigin) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .files(origin)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|file| model_name(&file.source))
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Fetches the file which declares the model.
+    fn fetch(&self, name: &str, origin: &UseOrigin) -> Option<ModelFile> {
+        self.files(origin)?
+            .into_iter()
+            .find(|file| model_name(&file.source).as_deref() == Some(name))
+    }
+
+    /// Finds the origin of the file, if the file would belong to the source.
+    ///
+    /// The changed files are read again only if their origins are found.
+    fn origin_of(&self, _path: &Path) -> Option<UseOrigin> {
+        None
+    }
+}
+
+/// The models embedded into the binary, such as the std models.
+#[derive(Debug)]
+pub struct EmbeddedSource {
+    dir: &'static Dir<'static>,
+}
+
+impl EmbeddedSource {
+    pub fn new(dir: &'static Dir<'static>) -> Self {
+        Self { dir }
+    }
+
+    /// The std models, which are always available.
+    pub fn std() -> Self {
+        Self::new(&STD_DIR)
+    }
+}
+
+impl ModelSource for EmbeddedSource {
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>> {
+        match origin {
+            UseOrigin::Local => Some(
+                self.dir
+                    .find("**/*.n3")
+                    .unwrap()
+                    .filter_map(|r| self.dir.get_file(r.path()))
+                    .filter_map(|f| Some(ModelFile::new(f.path(), f.contents_utf8()?)))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// The models under a directory, with the user namespaces under its `users` directory.
+#[derive(Debug)]
+pub struct DirSource {
+    path: PathBuf,
+}
+
+impl DirSource {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ModelSource for DirSource {
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>> {
+        let users = self.path.join(USERS_DIR);
+        match origin {
+            // user namespaces are loaded on demand
+            UseOrigin::Local => Some(
+                read_dir_files(&self.path)
+                    .into_iter()
+                    .filter(|file| !file.path.starts_with(&users))
+                    .collect(),
+            ),
+            UseOrigin::User(user) => {
+                let namespace = users.join(user);
+                if namespace.is_dir() {
+                    Some(read_dir_files(namespace))
+                } else {
+                    None
+                }
+            }
+            UseOrigin::Site(_) => None,
+        }
+    }
+
+    fn origin_of(&self, path: &Path) -> Option<UseOrigin> {
+        let users = self.path.join(USERS_DIR);
+        match path.strip_prefix(&users) {
+            Ok(path) => {
+                let user = path.components().next()?.as_os_str().to_str()?;
+                Some(UseOrigin::User(user.to_string()))
+            }
+            Err(_) if path.starts_with(&self.path) => Some(UseOrigin::Local),
+            Err(_) => None,
+        }
+    }
+}
+
+/// The models given in memory, such as the ones from an editor.
+#[derive(Debug, Default)]
+pub struct MemorySource {
+    files: Vec<(UseOrigin, ModelFile)>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the model file, replacing the one of the same origin and path.
+    pub fn insert<P: AsRef<Path>>(&mut self, origin: UseOrigin, path: P, source: &str) {
+        let file = ModelFile::new(path, source);
+        self.files
+            .retain(|(o, f)| !(is_same_origin(o, &origin) && f.path == file.path));
+        self.files.push((origin, file));
+    }
+}
+
+impl ModelSource for MemorySource {
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>> {
+        let files: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(o, _)| is_same_origin(o, origin))
+            .map(|(_, file)| file.clone())
+            .collect();
+
+        // the local models are always available, even if empty
+        match origin {
+            UseOrigin::Local => Some(files),
+            _ if files.is_empty() => None,
+            _ => Some(files),
+        }
+    }
+}
+
+/// The models packed into a tar archive, laid out like a [`DirSource`], with the site packages
+/// under its `sites` directory laid out like a [`RegistrySource`].
+///
+/// The files are read at once, and their paths are written under the path of the archive.
+///
+/// Only the uncompressed ustar and GNU tar archives are read, by a minimal reader of their
+/// regular files and GNU long names. The other entries, such as the links and the PAX headers,
+/// are skipped, so the compressed archives should be decompressed beforehand.
+#[derive(Debug)]
+pub struct ArchiveSource {
+    local: Vec<ModelFile>,
+    users: Vec<(String, ModelFile)>,
+    /// The files of the site packages, with their sites and versions.
+    sites: Vec<(String, String, ModelFile)>,
+}
+
+impl ArchiveSource {
+    /// Reads the tar archive from the file.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let archive = fs::read(&path)?;
+        Self::from_tar(path, &archive)
+    }
+
+    /// Reads the tar archive, where the paths of the files are written under `path`.
+    pub fn from_tar<P: AsRef<Path>>(path: P, archive: &[u8]) -> io::Result<Self> {
+        let mut local = vec![];
+        let mut users = vec![];
+        let mut sites = vec![];
+        for (name, contents) in read_tar(archive)? {
+            let name = name.trim_start_matches("./");
+            if !name.ends_with(".n3") {
+                continue;
+            }
+            let source = String::from_utf8(contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            let file = ModelFile::new(path.as_ref().join(name), &source);
+
+            let components: Vec<_> = name.splitn(4, '/').collect();
+            match components.as_slice() {
+                [USERS_DIR, user, _, ..] => users.push((user.to_string(), file)),
+                [SITES_DIR, site, version, _] => {
+                    sites.push((site.to_string(), version.to_string(), file))
+                }
+                _ => local.push(file),
+            }
+        }
+        Ok(Self {
+            local,
+            users,
+            sites,
+        })
+    }
+}
+
+impl ModelSource for ArchiveSource {
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>> {
+        match origin {
+            UseOrigin::Local => Some(self.local.clone()),
+            UseOrigin::User(user) => {
+                let files: Vec<_> = self
+                    .users
+                    .iter()
+                    .filter(|(u, _)| u == user)
+                    .map(|(_, file)| file.clone())
+                    .collect();
+                Some(files).filter(|files| !files.is_empty())
+            }
+            UseOrigin::Site(site) => {
+                let (site, version) = split_site(site);
+                let packages = self.sites.iter().filter(|(s, _, _)| s == site);
+                let version = match version {
+                    Some(version) => version.to_string(),
+                    None => latest_version(packages.clone().map(|(_, v, _)| v.clone()))?,
+                };
+                let files: Vec<_> = packages
+                    .filter(|(_, v, _)| *v == version)
+                    .map(|(_, _, file)| file.clone())
+                    .collect();
+                Some(files).filter(|files| !files.is_empty())
+            }
+        }
+    }
+}
+
+/// The site packages of a registry, laid out as `<registry>/<site>/<version>/**/*.n3`.
+///
+/// A site may be pinned to a version with `"site@version"`,
+/// otherwise the highest version is used.
+#[derive(Debug)]
+pub struct RegistrySource {
+    path: PathBuf,
+}
+
+impl RegistrySource {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ModelSource for RegistrySource {
+    fn files(&self, origin: &UseOrigin) -> Option<Vec<ModelFile>> {
+        let (site, version) = match origin {
+            UseOrigin::Site(site) => split_site(site),
+            _ => return None,
+        };
+        let site_dir = self.path.join(site);
+
+        let version = match version {
+            Some(version) => version.to_string(),
+            None => latest_version(
+                fs::read_dir(&site_dir)
+                    .ok()?
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_dir())
+                    .filter_map(|e| e.file_name().into_string().ok()),
+            )?,
+        };
+        let package = site_dir.join(version);
+        if package.is_dir() {
+            Some(read_dir_files(package))
+        } else {
+            None
+        }
+    }
+}
+
+impl ModelFile {
+    pub fn new<P: AsRef<Path>>(path: P, source: &str) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            source: source.to_string(),
+        }
+    }
+}
+
+fn model_name(source: &str) -> Option<String> {
+    parser::parse_file(source).ok().map(|ast| ast.model.name)
+}
+
+/// Splits the site into its name and its pinned version, if any.
+fn split_site(site: &str) -> (&str, Option<&str>) {
+    let mut site = site.splitn(2, '@');
+    (site.next().unwrap_or_default(), site.next())
+}
+
+/// Picks the highest version like `0.2.10`, compared numerically component by component.
+///
+/// The names which are not versions, such as `latest`, are not picked.
+fn latest_version<I: IntoIterator<Item = String>>(names: I) -> Option<String> {
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let version: Vec<u64> = name
+                .split('.')
+                .map(|c| c.parse().ok())
+                .collect::<Option<_>>()?;
+            Some((version, name))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, name)| name)
+}
+
+fn is_same_origin(a: &UseOrigin, b: &UseOrigin) -> bool {
+    match (a, b) {
+        (UseOrigin::Site(a), UseOrigin::Site(b)) => a == b,
+        (UseOrigin::User(a), UseOrigin::User(b)) => a == b,
+        (UseOrigin::Local, UseOrigin::Local) => true,
+        _ => false,
+    }
+}
+
+/// Reads the regular files of the ustar or GNU tar archive, with their names.
+///
+/// This is not a full tar reader: the entries other than the regular files and the GNU long
+/// names are skipped.
+fn read_tar(archive: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    const BLOCK: usize = 512;
+
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let text = |bytes: &[u8]| {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+
+    let mut files = vec![];
+    let mut long_name = None;
+    let mut offset = 0;
+    while offset + BLOCK <= archive.len() {
+        let header = &archive[offset..offset + BLOCK];
+        // the archive ends with the empty blocks
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let size = text(&header[124..136]);
+        let size =
+            usize::from_str_radix(size.trim(), 8).map_err(|_| invalid("malformed tar size"))?;
+        let start = offset + BLOCK;
+        let contents = archive
+            .get(start..start + size)
+            .ok_or_else(|| invalid("truncated tar archive"))?;
+        // the contents are padded to the blocks
+        offset = start + size + (BLOCK - size % BLOCK) % BLOCK;
+
+        let name = match long_name.take() {
+            Some(name) => name,
+            None if &header[257..262] == b"ustar" && header[345] != 0 => {
+                format!("{}/{}", text(&header[345..500]), text(&header[..100]))
+            }
+            None => text(&header[..100]),
+        };
+        match header[156] {
+            b'0' | 0 => files.push((name, contents.to_vec())),
+            // the GNU long name of the next file
+            b'L' => long_name = Some(text(contents)),
+            _ => {}
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_dir_files<P: AsRef<Path>>(pwd: P) -> Vec<ModelFile> {
+    walkdir::WalkDir::new(pwd)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|r| !r.metadata().map(|m| m.is_dir()).unwrap_or(true))
+        .map(|r| r.path().to_path_buf())
+        .filter(|p| p.extension().map(|e| e == "n3").unwrap_or(false))
+        .filter_map(|p| {
+            let source = fs::read_to_string(&p).ok()?;
+            Some(ModelFile { path: p, source })
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn read_dir_files<P: AsRef<Path>>(pwd: P) -> Vec<ModelFile> {
+    println!(
+        "Loading models from a directory on wasm is not supported yet: {}",
+        pwd.as_ref().display()
+    );
+    vec![]
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use crate::graphs::Value;
+
+/// A suspicious but valid usage found while compiling a model.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompileWarning {
+    /// A variable declared but never used in the graph of the model.
+    UnusedVariable { model: String, variable: String },
+    /// A model imported by `use` but never attached to any node.
+    UnusedUse { model: String },
+    /// An extern child model which hides the imported model with the same name.
+    ShadowedModel { model: String },
+    /// A variable overridden to the value it already has.
+    RedundantVariable {
+        model: String,
+        variable: String,
+        value: Value,
+    },
+}
+
+impl fmt::Display for CompileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnusedVariable { model, variable } => {
+                write!(f, "unused variable {:?} (in [{}])", variable, model)
+            }
+            Self::UnusedUse { model } => write!(f, "unused model: {}", model),
+            Self::ShadowedModel { model } => {
+                write!(f, "extern model [{}] shadows the imported one", model)
+            }
+            Self::RedundantVariable {
+                model,
+                variable,
+                value,
+            } => write!(
+                f,
+                "variable {:?} is already {:?} (in [{}])",
+                variable, value, model
+            ),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Watches the model directory, and compiles the models again once their files are changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::error::CompileError;
+use crate::graphs::GraphRoot;
+
+use n3_parser::ast::UseOrigin;
+
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// The model file has been created, modified or removed.
+    Changed { path: PathBuf },
+    /// The watched model has been compiled.
+    Compiled { model: String },
+    /// The model files have failed to parse, or the watched model has failed to compile.
+    Failed {
+        model: Option<String>,
+        errors: Vec<CompileError>,
+    },
+}
+
+/// Polls the `.n3` files under the directory, so that no platform-specific notifier is needed.
+pub struct Watcher {
+    path: PathBuf,
+    models: Vec<String>,
+    files: BTreeMap<PathBuf, (SystemTime, u64)>,
+    /// The models which should be compiled on the next poll.
+    pending: BTreeSet<String>,
+    /// The models which have failed to compile, which may be fixed by any change.
+    failed: BTreeSet<String>,
+}
+
+impl Watcher {
+    /// Watches the local models under the directory, which are compiled on the first poll.
+    pub fn new<P: AsRef<Path>>(path: P, models: Vec<String>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            files: scan(&path),
+            path,
+            pending: models.iter().cloned().collect(),
+            failed: BTreeSet::new(),
+            models,
+        }
+    }
+
+    /// Checks the files once, and compiles the watched models which are affected by the changes,
+    /// or have failed to compile before.
+    pub fn poll(&mut self, root: &mut GraphRoot) -> Vec<WatchEvent> {
+        let mut events = vec![];
+
+        let files = scan(&self.path);
+        let changed: BTreeSet<_> = files
+            .iter()
+            .filter(|(path, stamp)| self.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path)
+            .chain(self.files.keys().filter(|path| !files.contains_key(*path)))
+            .cloned()
+            .collect();
+        self.files = files;
+
+        if !changed.is_empty() {
+            for path in changed {
+                root.invalidate(&path);
+                events.push(WatchEvent::Changed { path });
+            }
+            self.pending.append(&mut self.failed);
+
+            // the models affected by the valid files are compiled even if the others fail
+            let report = root.reload();
+            let keys = report.invalidated;
+            self.pending
+                .extend(self.models.iter().filter(|m| keys.contains(m)).cloned());
+            if !report.errors.is_empty() {
+                events.push(WatchEvent::Failed {
+                    model: None,
+                    errors: report.errors,
+                });
+            }
+        }
+
+        for model in std::mem::take(&mut self.pending) {
+            match root.check_graph(&model, UseOrigin::Local) {
+                Ok(_) => events.push(WatchEvent::Compiled { model }),
+                Err(errors) => {
+                    self.failed.insert(model.clone());
+                    events.push(WatchEvent::Failed {
+                        model: Some(model),
+                        errors,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// Polls the files with the interval, and passes the events to the callback
+    /// until it returns `false`.
+    pub fn run<F>(&mut self, root: &mut GraphRoot, interval: Duration, mut f: F)
+    where
+        F: FnMut(WatchEvent) -> bool,
+    {
+        loop {
+            for event in self.poll(root) {
+                if !f(event) {
+                    return;
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// Finds the model files, with their modified times and sizes.
+fn scan(path: &Path) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().map(|e| e == "n3").unwrap_or(false))
+        .filter_map(|e| {
+            let metadata = fs::metadata(e.path()).ok()?;
+            let stamp = (metadata.modified().ok()?, metadata.len());
+            Some((e.path().to_path_buf(), stamp))
+        })
+        .collect()
+}
@@ -0,0 +1,168 @@
+use n3_core::*;
+
+use symengine::ExpressionMap;
+
+static SOUECE: &str = "
+use Conv2d
+use Linear
+
+use ReLU
+use Softmax
+
+use Transform
+
+[LeNet]
+
+  * N: number of classes = 10
+
+  [Conv2d]
+    * kernel size = 5
+    * stride = 2
+    * padding = 2
+
+  #0 Input Gray image = Ic, H  , W
+  #1 Conv2d + ReLU    = 32, H/2, W/2
+  #2 Conv2d + ReLU    = 64, H/4, W/4
+  #3 Transform        = 64* H/4* W/4
+  #4 Linear + Softmax = N
+";
+
+fn placeholder(name: &str) -> DimKey {
+    DimKey::Placeholder(name.to_string(), true)
+}
+
+#[test]
+fn estimate_lenet_cost() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let cost = analysis::estimate_cost(graph).unwrap();
+    let [ic, h, w] = ["Ic", "H", "W"].map(|name| placeholder(name).to_expr());
+
+    let conv2d = &cost.nodes[&GraphId::new_first()];
+    assert_eq!(conv2d.params, ic.clone() * 800u64 + Expression::from(32u64));
+    assert_eq!(conv2d.macs, ic.clone() * h.clone() * w.clone() * 200u64);
+
+    let relu = &cost.nodes[&GraphId {
+        node: 1,
+        pass: 1,
+        repeat: 0,
+    }];
+    assert_eq!(relu.params, 0u64);
+    assert_eq!(relu.macs, 0u64);
+
+    assert_eq!(
+        cost.total.params,
+        ic.clone() * 800u64 + h.clone() * w.clone() * 40u64 + Expression::from(51306u64)
+    );
+    assert_eq!(
+        cost.total.macs,
+        ic * h.clone() * w.clone() * 200u64 + h * w * 3240u64
+    );
+
+    // bind the placeholders
+    let mut symbols = ExpressionMap::new();
+    symbols.insert(placeholder("Ic"), 1u64);
+    symbols.insert(placeholder("H"), 28u64);
+    symbols.insert(placeholder("W"), 28u64);
+    assert_eq!(symbols.eval_once(&cost.total.params), 83466u64);
+    assert_eq!(symbols.eval_once(&cost.total.macs), 2696960u64);
+}
+
+#[test]
+fn estimate_std_cost_only() {
+    static SOUECE: &str = "
+use Conv2d
+use Transform
+
+[Biasless]
+
+  [Conv2d]
+    * kernel size = 3
+    * padding = 1
+    * bias = 0
+
+  extern [Linear]
+    * bias = 1
+
+    #0 fixed = Ic
+    #1 fixed = Oc
+
+  #0 Input     = 1, 4, 4
+  #1 Conv2d    = 8, 4, 4
+  #2 Transform = 128
+  #3 Linear    = 10
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    // the bias of Conv2d is not counted, nor the user model of the same name as Linear
+    let cost = analysis::estimate_cost(graph).unwrap();
+    assert_eq!(cost.nodes[&GraphId::new_first()].params, 72u64);
+    let linear = GraphId {
+        node: 3,
+        pass: 0,
+        repeat: 0,
+    };
+    assert_eq!(cost.nodes[&linear].params, 0u64);
+    assert_eq!(cost.total.params, 72u64);
+}
+
+#[test]
+fn estimate_lenet_memory() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let memory = analysis::estimate_memory(graph, 4, Dim::batch());
+    let batch = Dim::batch().to_expr();
+    let [ic, h, w] = ["Ic", "H", "W"].map(|name| placeholder(name).to_expr());
+
+    let input = &memory.outputs[&GraphId::new_input()];
+    let input_size = batch.clone() * ic * h.clone() * w.clone() * 4u64;
+    assert_eq!(input, &[input_size.clone()]);
+
+    let conv2d = GraphId::new_first();
+    let conv2d_size = batch * h * w * 32u64;
+    assert_eq!(memory.outputs[&conv2d], [conv2d_size.clone()]);
+    // the input is still alive while the first node runs
+    assert_eq!(memory.live[&conv2d], input_size + conv2d_size);
+
+    let mut symbols = ExpressionMap::new();
+    symbols.insert(DimKey::Batch, 1u64);
+    symbols.insert(placeholder("Ic"), 1u64);
+    symbols.insert(placeholder("H"), 28u64);
+    assert!(memory.peak(&symbols).is_none()); // W is still free
+
+    symbols.insert(placeholder("W"), 28u64);
+    let (id, peak) = memory.peak(&symbols).unwrap();
+    assert_eq!(
+        id,
+        GraphId {
+            node: 1,
+            pass: 1,
+            repeat: 0,
+        }
+    );
+    assert_eq!(peak, 50176u64);
+}
+
+#[test]
+fn estimate_lenet_memory_with_batch() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let memory = analysis::estimate_memory(graph, 4, Dim::Expr(32u64.into()));
+    let [ic, h, w] = ["Ic", "H", "W"].map(|name| placeholder(name).to_expr());
+
+    let input = &memory.outputs[&GraphId::new_input()];
+    assert_eq!(input, &[ic * h * w * 128u64]);
+
+    // the batch is already bound
+    let mut symbols = ExpressionMap::new();
+    symbols.insert(placeholder("Ic"), 1u64);
+    symbols.insert(placeholder("H"), 28u64);
+    symbols.insert(placeholder("W"), 28u64);
+    let (_, peak) = memory.peak(&symbols).unwrap();
+    assert_eq!(peak, 50176u64 * 32);
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use n3_core::*;
+
+static SOUECE: &str = "
+use Linear
+use Transform
+
+[Classifier]
+
+  #0 Input     = 3, H, W
+  #1 Transform = 3* H* W
+  #2 Linear    = 10
+";
+
+#[test]
+fn batch_axis() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let shapes = graph.get_shapes(false);
+    let shapes_with_batch = graph.get_shapes(true);
+    for (id, shapes) in shapes {
+        let shapes_with_batch = &shapes_with_batch[&id];
+        for (dims, dims_with_batch) in shapes.iter().zip(shapes_with_batch) {
+            assert!(dims_with_batch[0].is_batch());
+            assert_eq!(&dims_with_batch[1..], dims.as_slice());
+        }
+    }
+}
+
+#[test]
+fn bind_batch_axis() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let values: HashMap<_, _> = vec![("H", 4), ("W", 4), ("batch", 32)]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    let instance = graph.instantiate(&values).unwrap();
+
+    let shapes = instance.get_shapes(true);
+    let last = shapes.values().next_back().unwrap();
+    assert_eq!(
+        last[0],
+        vec![Dim::Expr(32u64.into()), Dim::Expr(10u64.into())]
+    );
+}
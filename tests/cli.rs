@@ -0,0 +1,48 @@
+#![cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+
+use std::process::{Command, Output};
+
+fn n3c(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_n3c"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn cli_shapes() {
+    let output = n3c(&["shapes", "models", "LeNet"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 8);
+    assert!(stdout.starts_with("#0         Input Gray image         [Ic, H, W]\n"));
+}
+
+#[test]
+fn cli_shapes_with_batch() {
+    let output = n3c(&["shapes", "--batch", "models", "LeNet"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("#0         Input Gray image         [batch, Ic, H, W]\n"));
+}
+
+#[test]
+fn cli_dump() {
+    let output = n3c(&["dump", "--format", "json", "models", "LeNet"]);
+    assert!(output.status.success());
+
+    let graph: n3_core::Graph = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(graph.get_shapes(false).len(), 8);
+}
+
+#[test]
+fn cli_exit_codes() {
+    let code = |args| n3c(args).status.code();
+
+    assert_eq!(code(&["check", "tests/broken/parse", "Bad Model"]), Some(2));
+    assert_eq!(code(&["check", "models", "Nothing"]), Some(3));
+    assert_eq!(code(&["check", "tests/broken/shape", "Bad Model"]), Some(4));
+    assert_eq!(code(&["check", "models", "LeNet"]), Some(0));
+}
@@ -0,0 +1,226 @@
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn pytorch_lenet() {
+    let mut path = std::env::current_dir().unwrap();
+    path.push("models");
+
+    let mut root = n3_core::GraphRoot::with_path(path).unwrap();
+
+    let graph = root.find_graph("LeNet", n3_core::UseOrigin::Local).unwrap();
+
+    let code = n3_core::codegen::pytorch("LeNet", graph).unwrap();
+    assert_eq!(code, include_str!("golden/lenet.py"));
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn onnx_lenet() {
+    let mut path = std::env::current_dir().unwrap();
+    path.push("models");
+
+    let mut root = n3_core::GraphRoot::with_path(path).unwrap();
+
+    let graph = root.find_graph("LeNet", n3_core::UseOrigin::Local).unwrap();
+
+    let model = n3_core::codegen::onnx("LeNet", graph).unwrap();
+    let model = read_message(&model);
+    assert_eq!(model[&1], [Field::Varint(7)]);
+
+    let graph = read_message(model[&7][0].unwrap_bytes());
+    let op_types: Vec<_> = graph[&1]
+        .iter()
+        .map(|node| read_string(&read_message(node.unwrap_bytes())[&4][0]))
+        .collect();
+    assert_eq!(
+        op_types,
+        ["Conv", "Relu", "Conv", "Relu", "Reshape", "Gemm", "Softmax"],
+    );
+
+    // the input with the named dynamic dimensions
+    let input = read_message(graph[&11][0].unwrap_bytes());
+    assert_eq!(read_string(&input[&1][0]), "x_0");
+    let ty = read_message(input[&2][0].unwrap_bytes());
+    let tensor_type = read_message(ty[&1][0].unwrap_bytes());
+    let shape = read_message(tensor_type[&2][0].unwrap_bytes());
+    let dims: Vec<_> = shape[&1]
+        .iter()
+        .map(|dim| read_string(&read_message(dim.unwrap_bytes())[&2][0]))
+        .collect();
+    assert_eq!(dims, ["batch", "Ic", "H", "W"]);
+}
+
+#[derive(Debug, PartialEq)]
+enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+impl Field {
+    fn unwrap_bytes(&self) -> &[u8] {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            _ => panic!("expected bytes: {:?}", self),
+        }
+    }
+}
+
+fn read_message(mut buf: &[u8]) -> std::collections::HashMap<u64, Vec<Field>> {
+    fn read_varint(buf: &mut &[u8]) -> u64 {
+        let mut value = 0;
+        for shift in (0..).step_by(7) {
+            let byte = buf[0];
+            *buf = &buf[1..];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                break;
+            }
+        }
+        value
+    }
+
+    let mut fields = std::collections::HashMap::<_, Vec<_>>::new();
+    while !buf.is_empty() {
+        let key = read_varint(&mut buf);
+        let field = match key & 0x7 {
+            0 => Field::Varint(read_varint(&mut buf)),
+            2 => {
+                let len = read_varint(&mut buf) as usize;
+                let bytes = buf[..len].to_vec();
+                buf = &buf[len..];
+                Field::Bytes(bytes)
+            }
+            wire => panic!("unexpected wire type: {}", wire),
+        };
+        fields.entry(key >> 3).or_default().push(field);
+    }
+    fields
+}
+
+fn read_string(field: &Field) -> String {
+    String::from_utf8(field.unwrap_bytes().to_vec()).unwrap()
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn dot_lenet_trainer() {
+    let mut path = std::env::current_dir().unwrap();
+    path.push("models");
+
+    let mut root = n3_core::GraphRoot::with_path(path).unwrap();
+
+    let graph = root
+        .find_graph("LeNet Trainer", n3_core::UseOrigin::Local)
+        .unwrap();
+
+    let code = n3_core::codegen::dot("LeNet Trainer", graph);
+    assert!(code.starts_with("digraph \"LeNet Trainer\" {\n"));
+
+    // nested cluster
+    assert!(code.contains("    subgraph \"cluster_#1\" {\n        label=\"#1 LeNet\";\n"));
+    assert!(code.contains("        \"#1/#1.1.0\" [label=\"#1.1.0 ReLU\"];\n"));
+    assert!(code.contains("        \"#1/#4\" -> \"#1/#4.1.0\" [label=\"10\"];\n"));
+
+    // edges into and out of the cluster, with the argument indices
+    assert!(
+        code.contains("    \"#0\" -> \"#1/#0\" [label=\"0: Icc, H, W\", lhead=\"cluster_#1\"];\n")
+    );
+    assert!(code.contains("    \"#1/#4.1.0\" -> \"#2\" [label=\"10\", ltail=\"cluster_#1\"];\n"));
+    assert!(code.contains("    \"#0\" -> \"#2\" [label=\"1: 10\"];\n"));
+}
+
+#[test]
+fn softmax_axis() {
+    static SOUECE: &str = "
+use Linear
+use Softmax
+
+[Softmax Model]
+    [Softmax]
+        * axis = -1
+
+    #0 Input = 4
+    #1 Linear + Softmax = 10
+";
+
+    let mut root = n3_core::GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    // the negative axis counts from the last one
+    let code = n3_core::codegen::pytorch("Softmax Model", graph).unwrap();
+    assert!(code.contains("nn.Softmax(dim=-1)"));
+
+    static OUT_OF_RANGE: &str = "
+use Linear
+use Softmax
+
+[Softmax Model]
+    #0 Input = 4
+    #1 Linear + Softmax (axis=1) = 10
+";
+
+    let graph = root.compile_from_source(OUT_OF_RANGE).unwrap();
+    match n3_core::codegen::pytorch("Softmax Model", graph) {
+        Err(n3_core::CodegenError::AxisOutOfRange {
+            axis: 1, rank: 1, ..
+        }) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn window_variables() {
+    static SOUECE: &str = "
+use Conv2d
+use MaxPool2d
+
+[Window Model]
+    #0 Input                = 3, H, W
+    #1 Conv2d (K=4)         = 8, H-3, W-3
+    #2 MaxPool2d (K=2)      = 8, (H-3)/2, (W-3)/2
+";
+
+    let mut root = n3_core::GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    // the variables are emitted as compiled
+    let code = n3_core::codegen::pytorch("Window Model", graph).unwrap();
+    assert!(code.contains("nn.Conv2d(3, 8, kernel_size=4, stride=1, padding=0)"));
+    assert!(code.contains("nn.MaxPool2d(kernel_size=2, stride=2, padding=0)"));
+
+    static UNSET: &str = "
+[Unset Model]
+    extern [Conv2d]
+        * K: kernel size
+        * stride = 1
+        * dilation = 1
+
+        #0 fixed = Ic, H, W
+        #1 fixed = Oc, H, W
+
+    #0 Input        = 3, H, W
+    #1 Conv2d (K=3) = 8, H, W
+";
+
+    let graph = root.compile_from_source(UNSET).unwrap();
+    match n3_core::codegen::pytorch("Unset Model", graph) {
+        Err(n3_core::CodegenError::NoVariableValue { name, .. }) => assert_eq!(name, "padding"),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn dot_escape() {
+    static SOUECE: &str = "
+use Linear
+
+[Sample Model]
+    #0 Input = 4
+    #1 Linear = 10
+";
+
+    let mut root = n3_core::GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let code = n3_core::codegen::dot("모델 \"v2\" \\ a", graph);
+    assert!(code.starts_with("digraph \"모델 \\\"v2\\\" \\\\ a\" {\n"));
+}
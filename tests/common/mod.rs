@@ -0,0 +1,44 @@
+//! The helpers shared by the tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A temporary directory of model files, which is removed once dropped, even if the test fails.
+pub struct Workspace {
+    path: PathBuf,
+}
+
+impl Workspace {
+    /// Creates the directory with the files, where the name should be unique among the tests.
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let mut path = std::env::temp_dir();
+        path.push(format!("n3-core-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+
+        for (file, source) in files {
+            fs::write(path.join(file), source).unwrap();
+        }
+        Self { path }
+    }
+}
+
+impl Deref for Workspace {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for Workspace {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use n3_core::*;
+
+#[test]
+fn conv_output_shapes() {
+    static SOUECE: &str = "
+use Conv2d
+use MaxPool2d
+
+[Conv Model]
+
+    #0 Input                        = 3, 32, 32
+    #1 Conv2d (K=3, S=2, P=1)       = 8, 16, 16
+    #2 Conv2d (K=3, P=0, D=2)       = 8, 12, 12
+    #3 MaxPool2d (K=2, S=2)         = 8,  6,  6
+    #4 Conv2d (K=3, S=2, P=0)       = 8,  2,  2
+    #5 MaxPool2d (K=2)              = 8,  1,  1
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let shapes = graph.get_shapes(false);
+    let last = shapes.values().next_back().unwrap();
+    assert_eq!(last[0][1], Dim::Expr(1u64.into()));
+}
+
+#[test]
+fn maxpool_default_stride() {
+    static SOUECE: &str = "
+use MaxPool2d
+
+[Pool Model]
+
+    #0 Input            = 8, 12, 12
+    #1 MaxPool2d (K=3)  = 8,  4,  4
+";
+
+    // the stride of the std `MaxPool2d` follows the kernel size
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let node = graph.get_nodes().values().next_back().unwrap();
+    let stride = &node.graph.as_ref().unwrap().get_variables()["stride"];
+    assert_eq!(stride.unwrap_uint(), Some(3));
+
+    let code = codegen::pytorch("Pool Model", graph).unwrap();
+    assert!(code.contains("nn.MaxPool2d(kernel_size=3, stride=3, padding=0)"));
+}
+
+#[test]
+fn user_window_stride() {
+    static SOUECE: &str = "
+[Pool Model]
+    extern [MaxPool2d]
+        * K: kernel size = 1
+        * S: stride
+
+        #0 fixed = N
+        #1 fixed = N
+
+    #0 Input            = 4
+    #1 MaxPool2d (K=3)  = 4
+";
+
+    // the user models of the same names are not given the default
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let node = graph.get_nodes().values().next_back().unwrap();
+    let child = node.graph.as_ref().unwrap();
+    assert_eq!(child.get_std_model(), None);
+    assert_eq!(child.get_variables()["stride"].value, None);
+}
+
+#[test]
+fn conv_floor_division() {
+    static SOUECE: &str = "
+use Conv2d
+
+[Conv Model]
+    [Conv2d]
+        * kernel size = 3
+        * stride = 2
+        * padding = 0
+
+    #0 Input  = 1, H, W
+    #1 Conv2d = 8, (H-1)/2, (W-1)/2
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    // (29 - 3) / 2 + 1 = 14
+    let values: HashMap<_, _> = vec![("H".to_string(), 29), ("W".to_string(), 29)]
+        .into_iter()
+        .collect();
+    assert!(graph.instantiate(&values).is_ok());
+
+    // (28 - 1) / 2 is not an integer
+    let values: HashMap<_, _> = vec![("H".to_string(), 28), ("W".to_string(), 28)]
+        .into_iter()
+        .collect();
+    let error = graph.instantiate(&values);
+    match error {
+        Err(GraphError::NonIntegerDimension { id, axis: 1, .. }) => {
+            assert_eq!(id, GraphId::new_first())
+        }
+        _ => panic!("unexpected result: {:?}", error.map(|_| ())),
+    }
+}
+
+#[test]
+fn conv_different_dimension() {
+    static SOUECE: &str = "
+use Conv2d
+
+[Conv Model]
+    [Conv2d]
+        * kernel size = 5
+        * stride = 2
+        * padding = PADDING
+
+    #0 Input  = 1, H, W
+    #1 Conv2d = 32, DIM, W/2
+";
+
+    // the declared dimensions are tested without binding the placeholders
+    for (padding, dim) in &[("2", "H/7"), ("0", "H/2")] {
+        let source = SOUECE.replace("PADDING", padding).replace("DIM", dim);
+        let mut root = GraphRoot::default();
+        match root.compile_from_source(&source).unwrap_err().inner() {
+            CompileError::GraphError {
+                error: GraphError::DifferentDimension { axis: 1, .. },
+                ..
+            } => {}
+            error => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    // floor((H - 1) / 2) + 1 = H / 2 if H / 2 is an integer
+    let source = SOUECE.replace("PADDING", "2").replace("DIM", "H/2");
+    let mut root = GraphRoot::default();
+    root.compile_from_source(&source).unwrap();
+}
@@ -0,0 +1,161 @@
+use n3_core::*;
+
+#[test]
+fn shape_error_span() {
+    static SOUECE: &str = "
+use Conv2d
+
+[Bad Model]
+    [Conv2d]
+        * kernel size = 5
+        * stride = 2
+
+    #0 Input  = 1, 28, 28
+    #1 Conv2d = 32, 15, 14
+";
+
+    let mut root = GraphRoot::default();
+
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDimension { axis, .. },
+            ..
+        } => assert_eq!(*axis, 1),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    let span = error.span().unwrap();
+    assert_eq!(span.line, 10);
+    assert_eq!(span.column, 5);
+    assert_eq!(span.snippet.trim(), "#1 Conv2d = 32, 15, 14");
+
+    let rendered = error.render();
+    assert!(rendered.starts_with("error: different dimension at #1:0 axis 1"));
+    assert!(rendered.contains("--> <source>:10:5"));
+    assert!(rendered.ends_with("|     ^^^^^^^^^^^^^^^^^^^^^^"));
+}
+
+#[test]
+fn variable_error_span() {
+    static SOUECE: &str = "
+use Linear
+
+[Bad Model]
+    #0 Input = 42
+    #1 Linear (unknown=3) = 12
+";
+
+    let mut root = GraphRoot::default();
+
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::NoSuchVariable { name },
+            ..
+        } => assert_eq!(name, "unknown"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    assert_eq!(error.span().unwrap().line, 6);
+}
+
+#[test]
+fn parse_error_span() {
+    static SOUECE: &str = "
+[Bad Model
+    #0 Input = 42
+";
+
+    let mut root = GraphRoot::default();
+
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::ParseError { .. } => {}
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    assert!(error.span().is_some());
+    assert!(error.render().contains('^'));
+}
+
+#[test]
+fn short_variable_error_span() {
+    static SOUECE: &str = "
+use Linear
+
+[Bad Model]
+    * Size: image size = 28
+
+    #0 Input = 28
+    #1 Linear (S=2) = 12
+";
+
+    let mut root = GraphRoot::default();
+
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::NoSuchVariable { name },
+            ..
+        } => assert_eq!(name, "S"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    // not the line of `Size`, which has the letter
+    assert_eq!(error.span().unwrap().line, 8);
+}
+
+#[test]
+fn nested_error_span() {
+    static SOUECE: &str = "
+use Linear
+
+[Outer Model]
+    [Block]
+        #0 Input  = BLOCK
+        #1 Linear = 8
+
+    #0 Input  = OUTER
+    #1 Linear = 8
+";
+
+    // the child reuses the node id, but only the block of the failed model is searched
+    for (block, outer, line) in &[("4", "4, 4", 10), ("4, 4", "4", 7)] {
+        let source = SOUECE.replace("BLOCK", block).replace("OUTER", outer);
+        let mut root = GraphRoot::default();
+
+        let error = root.compile_from_source(&source).unwrap_err();
+        match error.inner() {
+            CompileError::GraphError { error, .. } => {
+                assert_eq!(error.id().map(|id| id.node), Some(1))
+            }
+            _ => panic!("unexpected error: {:?}", error),
+        }
+
+        let span = error.span().unwrap();
+        assert_eq!(span.line, *line);
+        assert_eq!(span.snippet.trim(), "#1 Linear = 8");
+    }
+}
+
+#[test]
+fn use_error_span() {
+    static SOUECE: &str = "
+use Conv2d
+use Conv
+
+[Bad Model]
+    #0 Input = 1, 28, 28
+    #1 Conv2d (K=3) = 8, 26, 26
+";
+
+    let mut root = GraphRoot::default();
+
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::ModelError { model, .. } => assert_eq!(model, "Conv"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    // not the line of `Conv2d`, which starts with the name
+    let span = error.span().unwrap();
+    assert_eq!(span.line, 3);
+    assert_eq!(span.snippet.trim(), "use Conv");
+}
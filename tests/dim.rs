@@ -0,0 +1,59 @@
+use n3_core::*;
+
+use symengine::{Expression, ExpressionMap};
+
+fn dim(value: u64) -> Dim {
+    Dim::Expr(value.into())
+}
+
+#[test]
+fn floor_division() {
+    assert_eq!(dim(7).floor_div(dim(2)), dim(3));
+    assert_eq!(dim(8).floor_div(dim(2)), dim(4));
+
+    let h = Dim::Expr(Expression::new("H"));
+    let floor = h.floor_div(dim(2)) + dim(1);
+
+    let mut symbols = ExpressionMap::new();
+    symbols.insert("H", 29u64);
+    assert_eq!(symbols.eval_once(&floor.to_expr()), Expression::from(15u64));
+}
+
+#[test]
+fn divide_by_zero() {
+    static SOUECE: &str = "
+[Zero Model]
+    extern [Zero]
+        * S: stride = STRIDE
+
+        #0 fixed = N, H
+        #1 fixed = N, H / DIVISOR
+
+    #0 Input = 1, 4
+    #1 Zero  = 1, 2
+";
+
+    // the divisors are tested with the values of the variables
+    for (stride, divisor) in &[("1", "0"), ("0", "S")] {
+        let source = SOUECE.replace("STRIDE", stride).replace("DIVISOR", divisor);
+        let mut root = GraphRoot::default();
+        match root.compile_from_source(&source).unwrap_err().inner() {
+            CompileError::GraphError {
+                error: GraphError::DivideByZero { arg: 0, .. },
+                ..
+            } => {}
+            error => panic!("unexpected error: {:?}", error),
+        }
+    }
+}
+
+#[test]
+fn display_names() {
+    let h = Dim::Key(DimKey::Placeholder("H".to_string(), true));
+    let classes = Dim::Key(DimKey::Variable("number of classes".to_string()));
+
+    let display = (h * dim(2) + classes).to_string();
+    assert!(display.contains('H'));
+    assert!(display.contains("number of classes"));
+    assert!(!display.contains("ph_") && !display.contains("var_"));
+}
@@ -0,0 +1,142 @@
+use n3_core::*;
+
+#[test]
+fn propagate_dtypes() {
+    static SOUECE: &str = "
+use Linear
+use ReLU
+use Transform
+
+[Embedding]
+
+  #0 Input (i64 = 0)      = 4
+  #1 ReLU                 = 4
+  #2 Transform (f16 = 0)  = 4
+  #3 Linear               = 10
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let dtypes: Vec<_> = graph
+        .get_dtypes()
+        .values()
+        .map(|dtypes| dtypes.get(&0).copied())
+        .collect();
+    assert_eq!(
+        dtypes,
+        vec![
+            Some(DType::I64),
+            Some(DType::I64),
+            Some(DType::F16),
+            Some(DType::F16),
+        ]
+    );
+}
+
+#[test]
+fn float_models() {
+    static SOUECE: &str = "
+use Linear
+
+[Embedding]
+
+  #0 Input (i64 = 0) = 4
+  #1 Linear          = 10
+";
+
+    let mut root = GraphRoot::default();
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error:
+                GraphError::DifferentDType {
+                    id,
+                    arg,
+                    expected,
+                    given,
+                },
+            ..
+        } => {
+            assert_eq!(*id, GraphId::new_input());
+            assert_eq!(*arg, 0);
+            assert_eq!(*expected, DType::F32);
+            assert_eq!(*given, DType::I64);
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn different_dtypes() {
+    static SOUECE: &str = "
+use ReLU
+
+[Activation]
+
+  #0 Input (f16 = 0) = 4
+  #1 ReLU (f32 = 0)  = 4
+";
+
+    let mut root = GraphRoot::default();
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDType { id, .. },
+            ..
+        } => assert_eq!(*id, GraphId::new_first()),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn reserved_dtypes() {
+    static SOUECE: &str = "
+[Half Precision]
+
+  extern [Scale]
+    * f16: scale = 1
+
+    #0 dynamic
+    #1 identity
+
+  #0 Input  = 4
+  #1 Scale  = 4
+";
+
+    let mut root = GraphRoot::default();
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::ReservedVariable { name },
+            ..
+        } => assert_eq!(name, "f16"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    assert_eq!(error.span().unwrap().line, 5);
+
+    // only the std models cast, even if the users declare the same ones
+    static SOUECE_CAST: &str = "
+[Half Precision]
+
+  extern [Transform]
+    #0 dynamic
+    #1 dynamic (transform=yes)
+
+  #0 Input (f32 = 0)         = 4
+  #1 Transform (f16 = 0)     = 4
+";
+
+    let error = root.compile_from_source(SOUECE_CAST).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDType { id, .. },
+            ..
+        } => assert_eq!(*id, GraphId::new_first()),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    // nor is the behavior a variable
+    let graph = root.find_graph("Transform", UseOrigin::Local).unwrap();
+    assert!(graph.get_variables().is_empty());
+}
@@ -0,0 +1,73 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::fs;
+
+use n3_core::{CompileError, GraphRoot, UseOrigin};
+
+use common::Workspace;
+
+static NET: &str = "
+use ReLU
+
+[Net]
+
+  #0 Input = 4
+  #1 ReLU
+";
+
+static LINEAR: &str = "
+use ReLU
+
+[Linear]
+
+  #0 Input = 4
+  #1 ReLU
+";
+
+#[test]
+fn duplicated_local_models() {
+    let path = Workspace::new("duplicated", &[("a.n3", NET), ("b.n3", NET)]);
+
+    match GraphRoot::with_path(&path) {
+        Err(CompileError::DuplicatedModel {
+            model,
+            first,
+            second,
+        }) => {
+            assert_eq!(model, "Net");
+            assert_eq!(first, path.join("a.n3"));
+            assert_eq!(second, path.join("b.n3"));
+        }
+        _ => panic!("the duplicated models should be reported"),
+    }
+}
+
+#[test]
+fn shadow_std_models() {
+    let path = Workspace::new("shadowing", &[("linear.n3", LINEAR)]);
+
+    match GraphRoot::with_path(&path) {
+        Err(CompileError::DuplicatedModel { model, .. }) => assert_eq!(model, "Linear"),
+        _ => panic!("the shadowed std model should be reported"),
+    }
+
+    let mut root = GraphRoot::with_path_shadowing_std(&path, &["Linear"]).unwrap();
+    let linear = root.find_graph("Linear", UseOrigin::Local).unwrap();
+    assert!(!linear.is_extern());
+}
+
+#[test]
+fn duplicated_on_reload() {
+    let path = Workspace::new("duplicated-reload", &[("a.n3", NET)]);
+    let mut root = GraphRoot::with_path(&path).unwrap();
+    root.find_graph("Net", UseOrigin::Local).unwrap();
+
+    fs::write(path.join("b.n3"), NET).unwrap();
+    root.invalidate(path.join("b.n3"));
+    match root.reload().errors.as_slice() {
+        [CompileError::DuplicatedModel { model, .. }] => assert_eq!(model, "Net"),
+        _ => panic!("the duplicated models should be reported"),
+    }
+}
@@ -0,0 +1,28 @@
+# Generated by n3-core from [LeNet].
+
+from torch import nn
+
+
+class LeNet(nn.Module):
+    def __init__(self, Ic, H, W):
+        super().__init__()
+        self.Ic = Ic
+        self.H = H
+        self.W = W
+
+        self.layer_1 = nn.Conv2d(Ic, 32, kernel_size=5, stride=2, padding=2)
+        self.layer_1_1_0 = nn.ReLU()
+        self.layer_2 = nn.Conv2d(32, 64, kernel_size=5, stride=2, padding=2)
+        self.layer_2_1_0 = nn.ReLU()
+        self.layer_4 = nn.Linear(int(4*H*W), 10, bias=True)
+        self.layer_4_1_0 = nn.Softmax(dim=-1)
+
+    def forward(self, x_0):
+        x_1 = self.layer_1(x_0)
+        x_1_1_0 = self.layer_1_1_0(x_1)
+        x_2 = self.layer_2(x_1_1_0)
+        x_2_1_0 = self.layer_2_1_0(x_2)
+        x_3 = x_2_1_0.reshape(x_2_1_0.size(0), int(4*self.H*self.W))
+        x_4 = self.layer_4(x_3)
+        x_4_1_0 = self.layer_4_1_0(x_4)
+        return x_4_1_0
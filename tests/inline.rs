@@ -19,7 +19,7 @@ use ReLU
 
     let graph = root.compile_from_source(SOUECE).unwrap();
 
-    let shapes = graph.get_shapes();
+    let shapes = graph.get_shapes(false);
     assert_eq!(shapes.len(), 4);
 
     let last_shapes = shapes.values().rev().next().unwrap();
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use n3_core::*;
+
+static SOUECE: &str = "
+use Conv2d
+use Linear
+
+use ReLU
+use Softmax
+
+use Transform
+
+[LeNet]
+
+  * N: number of classes = 10
+  * C: number of channels
+
+  [Conv2d]
+    * kernel size = 5
+    * stride = 2
+    * padding = 2
+
+  #0 Input Gray image = Ic, H  , W
+  #1 Conv2d + ReLU    = C , H/2, W/2
+  #2 Conv2d + ReLU    = 64, H/4, W/4
+  #3 Transform        = 64* H/4* W/4
+  #4 Linear + Softmax = N
+";
+
+fn values(values: &[(&str, u64)]) -> HashMap<String, u64> {
+    values
+        .iter()
+        .map(|(name, value)| (name.to_string(), *value))
+        .collect()
+}
+
+fn dims(dims: &[u64]) -> Vec<Vec<Dim>> {
+    vec![dims.iter().map(|d| Dim::Expr((*d).into())).collect()]
+}
+
+#[test]
+fn instantiate_lenet() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let values = values(&[("Ic", 1), ("H", 28), ("W", 28), ("C", 32)]);
+    let instance = graph.instantiate(&values).unwrap();
+
+    let shapes = instance.get_shapes(false);
+    assert_eq!(shapes[&GraphId::new_input()], dims(&[1, 28, 28]));
+    assert_eq!(shapes[&GraphId::new_first()], dims(&[32, 14, 14]));
+    assert_eq!(shapes.values().nth(5).unwrap(), &dims(&[3136]));
+    assert_eq!(shapes.values().next_back().unwrap(), &dims(&[10]));
+
+    assert_eq!(
+        instance.get_variables()["number of channels"].value,
+        Some(Value::UInt(32)),
+    );
+
+    // the original graph is kept
+    assert_eq!(graph.get_variables()["number of channels"].value, None);
+}
+
+#[test]
+fn instantiate_errors() {
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let error = graph.instantiate(&values(&[("H", 28), ("W", 28), ("C", 32)]));
+    match error {
+        Err(GraphError::CannotEstimateShape {
+            id,
+            arg: 0,
+            axis: 0,
+        }) => {
+            assert_eq!(id, GraphId::new_input())
+        }
+        _ => panic!("unexpected result: {:?}", error.map(|_| ())),
+    }
+
+    let error = graph.instantiate(&values(&[("N", 12)]));
+    match error {
+        Err(GraphError::DifferentBinding {
+            name, given: 12, ..
+        }) => assert_eq!(name, "N"),
+        _ => panic!("unexpected result: {:?}", error.map(|_| ())),
+    }
+
+    let error = graph.instantiate(&values(&[("D", 12)]));
+    match error {
+        Err(GraphError::NoSuchVariable { name }) => assert_eq!(name, "D"),
+        _ => panic!("unexpected result: {:?}", error.map(|_| ())),
+    }
+}
+
+#[test]
+fn non_integer_dimensions() {
+    static SOUECE_FIXED: &str = "
+use Conv2d
+
+[Fixed Model]
+    [Conv2d]
+        * kernel size = 3
+        * stride = 3
+
+    #0 Input  = 1, 28, 28
+    #1 Conv2d = 8, 9, 9
+    #2 Conv2d = 8, 9/2, 9/2
+";
+
+    let mut root = GraphRoot::default();
+    let error = root.compile_from_source(SOUECE_FIXED).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::NonIntegerDimension { id, axis: 1, .. },
+            ..
+        } => assert_eq!(id.node, 2),
+        error => panic!("unexpected error: {:?}", error),
+    }
+
+    // H / 4 is not an integer
+    let graph = root.compile_from_source(SOUECE).unwrap();
+    let error = graph.instantiate(&values(&[("Ic", 1), ("H", 30), ("W", 28), ("C", 32)]));
+    match error {
+        Err(GraphError::NonIntegerDimension { id, axis: 1, .. }) => assert_eq!(id.node, 2),
+        _ => panic!("unexpected result: {:?}", error.map(|_| ())),
+    }
+}
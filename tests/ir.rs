@@ -17,6 +17,7 @@ use Transform
     [Conv2d]
         * kernel size = 5
         * stride = 2
+        * padding = 2
 
     #0 Input                = Ic, 28, 28
     #1 Conv2d (#0) + ReLU   = 32, 14, 14
@@ -48,7 +49,7 @@ use Transform
 
     // Nodes
     // note: if you want to get the shapes in each node,
-    // node: use `graph.get_shapes()` instead.
+    // node: use `graph.get_shapes(false)` instead.
 
     let mut nodes = graph.get_nodes().values();
 
@@ -117,7 +118,7 @@ use Transform
 
     // Shapes
 
-    let shapes = graph.get_shapes();
+    let shapes = graph.get_shapes(false);
     assert_eq!(shapes.len(), 8);
 
     let first_shapes = shapes.values().next().unwrap();
@@ -141,3 +142,94 @@ fn is_variable(dim: &Dim) -> bool {
         _ => false,
     }
 }
+
+#[cfg(feature = "ir")]
+#[test]
+fn make_ir_module() {
+    static SOUECE: &str = "
+use Conv2d
+use ReLU
+
+[Sample Model]
+
+    [Conv2d]
+        * kernel size = 3
+        * stride = 2
+        * padding = 1
+
+    #0 Input (f32 = 0)      = 1, H, W
+    #1 Conv2d (#0) + ReLU   = 8, H/2, W/2
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let module = ir::IrModule::new("Sample Model", graph);
+    assert_eq!(module.version, ir::IR_VERSION);
+
+    // Models
+
+    let names: Vec<_> = module.models.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["Sample Model", "Conv2d", "ReLU"]);
+    assert_eq!(module.models[0].is_extern, false);
+    assert_eq!(module.models[1].is_extern, true);
+    assert!(module.models[1].variables.contains(&ir::IrVariable {
+        name: "stride".to_string(),
+        ty: "uint".to_string(),
+        value: Some(ir::IrValue::UInt(2)),
+    }));
+
+    // Nodes and edges
+
+    let nodes: Vec<_> = module
+        .nodes
+        .iter()
+        .map(|n| (n.graph_id.as_str(), n.name.as_str(), n.graph))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("#0", "Input", None),
+            ("#1", "Conv2d", Some(1)),
+            ("#1.1.0", "ReLU", Some(2)),
+        ]
+    );
+
+    let edges: Vec<_> = module.edges.iter().map(|e| (e.from, e.to)).collect();
+    assert_eq!(edges, [(0, 1), (1, 2)]);
+
+    // Shapes
+
+    let shapes: Vec<_> = module.shapes.iter().map(|s| s.dims.join(", ")).collect();
+    assert_eq!(
+        shapes,
+        ["1, H, W", "8, (1/2)*H, (1/2)*W", "8, (1/2)*H, (1/2)*W"]
+    );
+
+    let dtypes: Vec<_> = module.shapes.iter().map(|s| s.dtype.as_deref()).collect();
+    assert_eq!(dtypes, [Some("f32"), Some("f32"), Some("f32")]);
+
+    // Import
+
+    let json = module.to_json();
+    assert_eq!(ir::IrModule::from_json(&json).unwrap(), module);
+
+    // the signed values are not read back as the unsigned ones
+    let mut signed = module.clone();
+    signed.models[0].variables.push(ir::IrVariable {
+        name: "axis".to_string(),
+        ty: "int".to_string(),
+        value: Some(ir::IrValue::Int(1)),
+    });
+    assert_eq!(ir::IrModule::from_json(&signed.to_json()).unwrap(), signed);
+
+    let json = json.replacen(
+        &format!("\"version\": {}", ir::IR_VERSION),
+        "\"version\": 0",
+        1,
+    );
+    match ir::IrModule::from_json(&json) {
+        Err(IrError::UnsupportedVersion { version: Some(0) }) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -10,11 +10,16 @@ fn local_model() {
         .find_graph("LeNet Trainer", n3_core::UseOrigin::Local)
         .unwrap();
 
-    let shapes = graph.get_shapes();
+    let shapes = graph.get_shapes(false);
     assert_eq!(shapes.len(), 3);
 
     let last_shapes = shapes.values().rev().next().unwrap();
     assert_eq!(last_shapes.len(), 1);
     assert_eq!(last_shapes[0].len(), 1);
     assert_eq!(last_shapes[0][0], n3_core::Dim::Expr(2u64.into()));
+
+    // the labels are integers
+    let dtypes = graph.get_dtypes();
+    let input_dtypes = dtypes.values().next().unwrap();
+    assert_eq!(input_dtypes.get(&1), Some(&n3_core::DType::I64));
 }
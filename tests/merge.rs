@@ -0,0 +1,150 @@
+use n3_core::*;
+
+fn compile(source: &str) -> Result<Graph, CompileError> {
+    let mut root = GraphRoot::default();
+    root.compile_from_source(source).map(Clone::clone)
+}
+
+fn last_dims(graph: &Graph) -> Vec<String> {
+    let shapes = graph.get_shapes(false);
+    let last = shapes.values().next_back().unwrap();
+    last[0].iter().map(|d| d.to_string()).collect()
+}
+
+#[test]
+fn residual_block() {
+    static SOUECE: &str = "
+use Add
+use Conv2d
+use ReLU
+
+[Residual Block]
+
+  [Conv2d]
+    * kernel size = 3
+    * padding = 1
+
+  #0 Input          = 64, H, W
+  #1 Conv2d + ReLU  = 64, H, W
+  #2 Conv2d         = 64, H, W
+  #3 Add (#0, #2)   = 64, H, W
+  #4 ReLU
+";
+
+    let graph = compile(SOUECE).unwrap();
+    assert_eq!(last_dims(&graph), ["64", "H", "W"]);
+
+    let add = graph
+        .get_nodes()
+        .values()
+        .find(|n| n.name == "Add")
+        .unwrap();
+    assert_eq!(add.inputs.len(), 2);
+}
+
+#[test]
+fn broadcast() {
+    static SOUECE: &str = "
+use Mul
+
+[Gate]
+
+  #0 Input            = [0: C, H, W], [1: C, 1, 1]
+  #1 Mul (#0:0, #0:1) = C, H, W
+";
+
+    let graph = compile(SOUECE).unwrap();
+    assert_eq!(last_dims(&graph), ["C", "H", "W"]);
+
+    static SOUECE_WRONG: &str = "
+use Mul
+
+[Gate]
+
+  #0 Input            = [0: 3, H, W], [1: 4, 1, 1]
+  #1 Mul (#0:0, #0:1)
+";
+
+    let error = compile(SOUECE_WRONG).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDimension { arg, axis, .. },
+            ..
+        } => assert_eq!((*arg, *axis), (1, 0)),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn concat() {
+    static SOUECE: &str = "
+use Concat
+
+[Stack]
+
+  #0 Input               = [0: 3, H, W], [1: 5, H, W]
+  #1 Concat (#0:0, #0:1) = 8, H, W
+";
+
+    let graph = compile(SOUECE).unwrap();
+    assert_eq!(last_dims(&graph), ["8", "H", "W"]);
+
+    static SOUECE_AXIS: &str = "
+use Concat
+
+[Stack]
+
+  #0 Input                       = [0: 3, H, W], [1: 5, H, W]
+  #1 Concat (#0:0, #0:1, axis=1)
+";
+
+    let error = compile(SOUECE_AXIS).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDimension { axis, .. },
+            ..
+        } => assert_eq!(*axis, 0),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    static SOUECE_RANGE: &str = "
+use Concat
+
+[Stack]
+
+  #0 Input                       = [0: 3, H, W], [1: 5, H, W]
+  #1 Concat (#0:0, #0:1, axis=3)
+";
+
+    let error = compile(SOUECE_RANGE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::AxisOutOfRange { axis, rank, .. },
+            ..
+        } => assert_eq!((*axis, *rank), (3, 3)),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    // the negative axis counts from the last one
+    static SOUECE_LAST: &str = "
+use Concat
+
+[Stack]
+
+  #0 Input                        = [0: 3, H, 2], [1: 3, H, 5]
+  #1 Concat (#0:0, #0:1, axis=-1) = 3, H, 7
+";
+
+    let graph = compile(SOUECE_LAST).unwrap();
+    assert_eq!(last_dims(&graph), ["3", "H", "7"]);
+
+    let source = SOUECE_RANGE.replace("axis=3", "axis=-4");
+    let error = compile(&source).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::AxisOutOfRange { axis, rank, .. },
+            ..
+        } => assert_eq!((*axis, *rank), (-4, 3)),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
@@ -0,0 +1,74 @@
+use n3_core::*;
+
+#[test]
+fn recover_errors() {
+    static SOUECE: &str = "
+use Conv2d
+use Nothing
+
+[Bad Model]
+    [Conv2d]
+        * kernel size = 5
+        * stride = 2
+
+    #0 Input  = 1, 28, 28
+    #1 Conv2d = 32, 15, 14
+    #2 Conv2d = 64, 15/2, 7
+    #3 Nothing = 10
+";
+
+    let mut root = GraphRoot::default();
+
+    let errors = root.check_from_source(SOUECE).unwrap_err();
+    let lines: Vec<_> = errors.iter().map(|e| e.span().unwrap().line).collect();
+    assert_eq!(lines, [3, 11, 12]);
+
+    match errors[0].inner() {
+        CompileError::ModelError {
+            model,
+            error: ModelError::ModelNotFound,
+            ..
+        } => assert_eq!(model, "Nothing"),
+        error => panic!("unexpected error: {:?}", error),
+    }
+    match errors[1].inner() {
+        CompileError::GraphError {
+            error: GraphError::DifferentDimension { .. },
+            ..
+        } => {}
+        error => panic!("unexpected error: {:?}", error),
+    }
+    // 15 / 2
+    match errors[2].inner() {
+        CompileError::GraphError {
+            error: GraphError::NonIntegerDimension { .. },
+            ..
+        } => {}
+        error => panic!("unexpected error: {:?}", error),
+    }
+
+    // the first error is still reported without recovering
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    assert_eq!(error.span().unwrap().line, 3);
+}
+
+#[test]
+fn recover_nothing() {
+    static SOUECE: &str = "
+use Conv2d
+
+[Good Model]
+    [Conv2d]
+        * kernel size = 5
+        * padding = 2
+        * stride = 2
+
+    #0 Input  = 1, 28, 28
+    #1 Conv2d = 32, 14, 14
+";
+
+    let mut root = GraphRoot::default();
+
+    let graph = root.check_from_source(SOUECE).unwrap();
+    assert_eq!(graph.get_nodes().len(), 2);
+}
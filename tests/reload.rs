@@ -0,0 +1,150 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::fs;
+
+use n3_core::{CompileError, GraphRoot, UseOrigin};
+
+use common::Workspace;
+
+static BACKBONE: &str = "
+use Linear
+
+[Backbone]
+
+  #0 Input  = 16
+  #1 Linear = 8
+";
+
+static BACKBONE_WIDER: &str = "
+use Linear
+
+[Backbone]
+
+  #0 Input  = 16
+  #1 Linear = 32
+";
+
+static HEAD: &str = "
+use Backbone
+use Linear
+
+[Head]
+
+  #0 Input    = 16
+  #1 Backbone = 8
+  #2 Linear   = 2
+";
+
+static UNRELATED: &str = "
+use ReLU
+
+[Unrelated]
+
+  #0 Input = 4
+  #1 ReLU
+";
+
+fn workspace(name: &str) -> Workspace {
+    Workspace::new(
+        name,
+        &[
+            ("backbone.n3", BACKBONE),
+            ("head.n3", HEAD),
+            ("unrelated.n3", UNRELATED),
+        ],
+    )
+}
+
+/// Reloads the changed files, which should not fail.
+fn reload(root: &mut GraphRoot) -> Vec<String> {
+    let report = root.reload();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    report.invalidated
+}
+
+#[test]
+fn reload_dependents() {
+    let path = workspace("reload");
+    let mut root = GraphRoot::with_path(&path).unwrap();
+
+    root.find_graph("Head", UseOrigin::Local).unwrap();
+    root.find_graph("Unrelated", UseOrigin::Local).unwrap();
+
+    fs::write(path.join("backbone.n3"), BACKBONE_WIDER).unwrap();
+    root.invalidate(path.join("backbone.n3"));
+    assert_eq!(reload(&mut root), ["Backbone", "Head"]);
+
+    // the head does not fit the new backbone anymore
+    assert!(root.find_graph("Head", UseOrigin::Local).is_err());
+    let backbone = root.find_graph("Backbone", UseOrigin::Local).unwrap();
+    let shapes = backbone.get_shapes(false);
+    assert_eq!(shapes.values().next_back().unwrap()[0][0].to_string(), "32");
+
+    // nothing is changed
+    assert!(reload(&mut root).is_empty());
+}
+
+#[test]
+fn reload_created_and_removed() {
+    let path = workspace("create");
+    let mut root = GraphRoot::with_path(&path).unwrap();
+    root.find_graph("Unrelated", UseOrigin::Local).unwrap();
+
+    fs::remove_file(path.join("unrelated.n3")).unwrap();
+    root.invalidate(path.join("unrelated.n3"));
+    assert_eq!(reload(&mut root), ["Unrelated"]);
+    assert!(root.find_graph("Unrelated", UseOrigin::Local).is_err());
+
+    fs::write(path.join("unrelated.n3"), UNRELATED).unwrap();
+    root.invalidate(path.join("unrelated.n3"));
+    assert!(reload(&mut root).is_empty());
+    assert!(root.find_graph("Unrelated", UseOrigin::Local).is_ok());
+}
+
+#[test]
+fn reload_failed_dependents() {
+    let path = workspace("failed");
+    fs::write(path.join("backbone.n3"), BACKBONE_WIDER).unwrap();
+    let mut root = GraphRoot::with_path(&path).unwrap();
+
+    // the head does not fit the backbone
+    assert!(root.find_graph("Head", UseOrigin::Local).is_err());
+
+    fs::write(path.join("backbone.n3"), BACKBONE).unwrap();
+    root.invalidate(path.join("backbone.n3"));
+    assert_eq!(reload(&mut root), ["Backbone"]);
+
+    // the head is unchanged, but compiled again with the fixed backbone
+    assert!(root.find_graph("Head", UseOrigin::Local).is_ok());
+}
+
+#[test]
+fn reload_with_broken_file() {
+    let path = workspace("broken");
+    let mut root = GraphRoot::with_path(&path).unwrap();
+
+    root.find_graph("Head", UseOrigin::Local).unwrap();
+    root.find_graph("Unrelated", UseOrigin::Local).unwrap();
+
+    // the keys are reported even if a file fails to parse
+    fs::write(path.join("backbone.n3"), BACKBONE_WIDER).unwrap();
+    fs::write(
+        path.join("unrelated.n3"),
+        UNRELATED.replace("[Unrelated]", "[Unrelated"),
+    )
+    .unwrap();
+    root.invalidate(path.join("backbone.n3"));
+    root.invalidate(path.join("unrelated.n3"));
+
+    let report = root.reload();
+    assert_eq!(report.invalidated, ["Backbone", "Head", "Unrelated"]);
+    match report.errors.as_slice() {
+        [error] => match error.inner() {
+            CompileError::ParseError { .. } => {}
+            error => panic!("unexpected error: {:?}", error),
+        },
+        errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
@@ -0,0 +1,45 @@
+use n3_core::*;
+
+#[test]
+fn route_dynamic_input() {
+    static SOUECE: &str = "
+use Linear
+
+[Classifier]
+
+  #0 Input
+  #1 Linear (#0) = 10
+";
+
+    let mut root = GraphRoot::default();
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let shapes = graph.get_shapes(false);
+    assert_eq!(shapes.len(), 2);
+    assert_eq!(
+        shapes.values().next_back().unwrap()[0][0],
+        Dim::Expr(10u64.into())
+    );
+}
+
+#[test]
+fn route_dynamic_input_arg() {
+    static SOUECE: &str = "
+use Linear
+
+[Classifier]
+
+  #0 Input
+  #1 Linear (#0:1) = 10
+";
+
+    let mut root = GraphRoot::default();
+    let error = root.compile_from_source(SOUECE).unwrap_err();
+    match error.inner() {
+        CompileError::GraphError {
+            error: GraphError::FullShapeRequired { id },
+            ..
+        } => assert_eq!(*id, GraphId::new_input()),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
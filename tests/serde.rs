@@ -20,7 +20,7 @@ use ReLU
         .find_graph("Sample Model", n3_core::UseOrigin::Local)
         .unwrap();
 
-    let shapes = graph.get_shapes();
+    let shapes = graph.get_shapes(false);
     assert_eq!(shapes.len(), 3);
 
     let last_shapes = shapes.values().rev().next().unwrap();
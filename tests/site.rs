@@ -0,0 +1,82 @@
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn site_model() {
+    static SOUECE: &str = "
+use MLP by \"podo\"
+use Linear
+
+[Site Model]
+    #0 Input = 28
+    #1 MLP = 10
+    #2 Linear = 2
+";
+
+    let mut registry = std::env::current_dir().unwrap();
+    registry.push("tests");
+    registry.push("registry");
+
+    let mut root = n3_core::GraphRoot::default();
+    root.set_site_registry(registry);
+
+    let graph = root.compile_from_source(SOUECE).unwrap();
+
+    let shapes = graph.get_shapes(false);
+    assert_eq!(shapes.len(), 3);
+
+    // the highest version (0.10.0) is picked by default
+    let mlp = graph.get_nodes().values().nth(1).unwrap();
+    let mlp_shapes = mlp.graph.as_ref().unwrap().get_shapes(false);
+    let hidden_shapes = mlp_shapes.values().nth(2).unwrap();
+    assert_eq!(hidden_shapes[0][0], n3_core::Dim::Expr(128u64.into()));
+
+    let mlp = root
+        .find_graph("MLP", n3_core::UseOrigin::Site("podo@0.2.0".to_string()))
+        .unwrap();
+    let hidden_shapes = mlp.get_shapes(false).values().nth(2).unwrap().clone();
+    assert_eq!(hidden_shapes[0][0], n3_core::Dim::Expr(64u64.into()));
+}
+
+#[test]
+fn site_not_found() {
+    let mut root = n3_core::GraphRoot::default();
+
+    let error = root
+        .find_graph("MLP", n3_core::UseOrigin::Site("podo".to_string()))
+        .unwrap_err();
+    match error {
+        n3_core::CompileError::ModelError { error, .. } => {
+            assert!(matches!(error, n3_core::ModelError::SiteNotFound))
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn site_model_uses_siblings() {
+    static SOUECE: &str = "
+use Linear
+
+[MLP]
+    #0 Input = Ic
+    #1 Linear = 10
+";
+
+    let mut registry = std::env::current_dir().unwrap();
+    registry.push("tests");
+    registry.push("registry");
+
+    let mut root = n3_core::GraphRoot::default();
+    root.set_site_registry(registry);
+
+    // the local model of the same name is not used by the package
+    root.compile_from_source(SOUECE).unwrap();
+
+    let deep = root
+        .find_graph("Deep MLP", n3_core::UseOrigin::Site("podo".to_string()))
+        .unwrap();
+    let mlp = deep.get_nodes().values().nth(1).unwrap();
+    let mlp_shapes = mlp.graph.as_ref().unwrap().get_shapes(false);
+    let hidden_shapes = mlp_shapes.values().nth(2).unwrap();
+    assert_eq!(hidden_shapes[0][0], n3_core::Dim::Expr(128u64.into()));
+}
@@ -0,0 +1,171 @@
+use n3_core::source::{ArchiveSource, EmbeddedSource, MemorySource, ModelSource};
+use n3_core::{GraphRoot, UseOrigin};
+
+static SOUECE: &str = "
+use Linear
+
+[Head]
+
+  #0 Input  = 16
+  #1 Linear = 8
+";
+
+static HEAD_WIDER: &str = "
+use Linear
+
+[Head]
+
+  #0 Input  = 16
+  #1 Linear = 32
+";
+
+static USER_MODEL: &str = "
+use Head
+
+[Classifier]
+
+  #0 Input = 16
+  #1 Head  = 8
+";
+
+fn output_dim(root: &mut GraphRoot, name: &str) -> String {
+    let graph = root.find_graph(name, UseOrigin::Local).unwrap();
+    let shapes = graph.get_shapes(false);
+    shapes.values().next_back().unwrap()[0][0].to_string()
+}
+
+#[test]
+fn memory_source_priority() {
+    let mut first = MemorySource::new();
+    first.insert(UseOrigin::Local, "first/head.n3", HEAD_WIDER);
+    let mut second = MemorySource::new();
+    second.insert(UseOrigin::Local, "second/head.n3", SOUECE);
+
+    assert_eq!(first.list(&UseOrigin::Local), ["Head"]);
+    assert!(second.fetch("Head", &UseOrigin::Local).is_some());
+    assert!(second.fetch("Tail", &UseOrigin::Local).is_none());
+
+    // the earlier source shadows the later one
+    let mut root = GraphRoot::with_sources(vec![
+        Box::new(first),
+        Box::new(second),
+        Box::new(EmbeddedSource::std()),
+    ])
+    .unwrap();
+    assert_eq!(output_dim(&mut root, "Head"), "32");
+}
+
+#[test]
+fn memory_source_user() {
+    let mut source = MemorySource::new();
+    source.insert(UseOrigin::Local, "head.n3", HEAD_WIDER);
+    source.insert(
+        UseOrigin::User("alice".to_string()),
+        "alice/classifier.n3",
+        USER_MODEL,
+    );
+    // the package uses its own sibling
+    source.insert(
+        UseOrigin::User("alice".to_string()),
+        "alice/head.n3",
+        SOUECE,
+    );
+
+    let mut root =
+        GraphRoot::with_sources(vec![Box::new(source), Box::new(EmbeddedSource::std())]).unwrap();
+    root.find_graph("Classifier", UseOrigin::User("alice".to_string()))
+        .unwrap();
+    assert!(root
+        .find_graph("Classifier", UseOrigin::User("bob".to_string()))
+        .is_err());
+}
+
+/// Packs the files into an uncompressed ustar archive.
+fn tar(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut archive = vec![];
+    for (name, contents) in files {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        let size = format!("{:011o}", contents.len());
+        header[124..135].copy_from_slice(size.as_bytes());
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(contents.as_bytes());
+        archive.resize((archive.len() + 511) / 512 * 512, 0);
+    }
+    archive.resize(archive.len() + 1024, 0);
+    archive
+}
+
+#[test]
+fn archive_source() {
+    let archive = tar(&[
+        ("head.n3", SOUECE),
+        ("README.md", "not a model"),
+        ("users/alice/classifier.n3", USER_MODEL),
+        ("users/alice/head.n3", SOUECE),
+    ]);
+    let source = ArchiveSource::from_tar("models.tar", &archive).unwrap();
+    assert_eq!(source.list(&UseOrigin::Local), ["Head"]);
+
+    let file = source
+        .fetch("Classifier", &UseOrigin::User("alice".to_string()))
+        .unwrap();
+    assert_eq!(
+        file.path.to_str(),
+        Some("models.tar/users/alice/classifier.n3")
+    );
+
+    let mut root =
+        GraphRoot::with_sources(vec![Box::new(source), Box::new(EmbeddedSource::std())]).unwrap();
+    assert_eq!(output_dim(&mut root, "Head"), "8");
+    root.find_graph("Classifier", UseOrigin::User("alice".to_string()))
+        .unwrap();
+
+    assert!(ArchiveSource::from_tar("broken.tar", &archive[..520]).is_err());
+}
+
+fn site_output_dim(root: &mut GraphRoot, name: &str, site: &str) -> String {
+    let graph = root
+        .find_graph(name, UseOrigin::Site(site.to_string()))
+        .unwrap();
+    let shapes = graph.get_shapes(false);
+    shapes.values().next_back().unwrap()[0][0].to_string()
+}
+
+#[test]
+fn archive_site_packages() {
+    let archive = tar(&[
+        ("sites/podo/0.2.0/head.n3", SOUECE),
+        ("sites/podo/0.10.0/head.n3", HEAD_WIDER),
+        ("sites/podo/latest/head.n3", SOUECE),
+    ]);
+    let source = ArchiveSource::from_tar("models.tar", &archive).unwrap();
+    // the site packages are not the local models
+    assert!(source.list(&UseOrigin::Local).is_empty());
+
+    let mut root =
+        GraphRoot::with_sources(vec![Box::new(source), Box::new(EmbeddedSource::std())]).unwrap();
+    // the highest version (0.10.0) is picked by default
+    assert_eq!(site_output_dim(&mut root, "Head", "podo"), "32");
+    assert_eq!(site_output_dim(&mut root, "Head", "podo@0.2.0"), "8");
+}
+
+#[test]
+fn memory_source_site() {
+    let mut memory = MemorySource::new();
+    memory.insert(UseOrigin::Site("podo".to_string()), "head.n3", HEAD_WIDER);
+
+    let mut root =
+        GraphRoot::with_sources(vec![Box::new(memory), Box::new(EmbeddedSource::std())]).unwrap();
+    // the site packages in memory are found along with the registry
+    let mut registry = std::env::current_dir().unwrap();
+    registry.push("tests");
+    registry.push("registry");
+    root.set_site_registry(registry);
+
+    assert_eq!(site_output_dim(&mut root, "Head", "podo"), "32");
+}
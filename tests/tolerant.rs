@@ -0,0 +1,98 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::fs;
+
+use n3_core::{CompileError, GraphRoot, ModelError, UseOrigin};
+
+use common::Workspace;
+
+static SOUECE: &str = "
+use Linear
+
+[Good Model]
+
+  #0 Input  = 16
+  #1 Linear = 8
+";
+
+static BROKEN: &str = "
+use Linear
+
+[Draft Model
+
+  #0 Input  = 16
+";
+
+static FIXED: &str = "
+use Linear
+
+[Draft Model]
+
+  #0 Input  = 16
+  #1 Linear = 4
+";
+
+fn workspace(name: &str) -> Workspace {
+    Workspace::new(name, &[("good.n3", SOUECE), ("draft.n3", BROKEN)])
+}
+
+#[test]
+fn broken_file_is_reported_on_request() {
+    let path = workspace("tolerant");
+    let mut root = GraphRoot::with_path(&path).unwrap();
+
+    // the broken file does not fail the others
+    root.find_graph("Good Model", UseOrigin::Local).unwrap();
+
+    let error = root
+        .find_graph("Draft Model", UseOrigin::Local)
+        .unwrap_err();
+    match error.inner() {
+        CompileError::ParseError {
+            path: error_path, ..
+        } => {
+            assert_eq!(error_path, &path.join("draft.n3"))
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    // the error is kept until the file is fixed
+    assert!(root.find_graph("Draft Model", UseOrigin::Local).is_err());
+
+    match root
+        .find_graph("Unknown Model", UseOrigin::Local)
+        .unwrap_err()
+    {
+        CompileError::ModelError {
+            error: ModelError::ModelNotFound,
+            ..
+        } => {}
+        error => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn broken_file_is_fixed_on_reload() {
+    let path = workspace("tolerant-reload");
+    let mut root = GraphRoot::with_path(&path).unwrap();
+    assert!(root.find_graph("Draft Model", UseOrigin::Local).is_err());
+
+    fs::write(path.join("draft.n3"), FIXED).unwrap();
+    root.invalidate(path.join("draft.n3"));
+    assert!(root.reload().errors.is_empty());
+    root.find_graph("Draft Model", UseOrigin::Local).unwrap();
+
+    // breaking the file again is reported on reload and on request
+    fs::write(path.join("good.n3"), BROKEN.replace("Draft", "Good")).unwrap();
+    root.invalidate(path.join("good.n3"));
+    assert_eq!(root.reload().errors.len(), 1);
+    match root
+        .find_graph("Good Model", UseOrigin::Local)
+        .unwrap_err()
+        .inner()
+    {
+        CompileError::ParseError { .. } => {}
+        error => panic!("unexpected error: {:?}", error),
+    }
+}
@@ -0,0 +1,28 @@
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn user_model() {
+    let mut path = std::env::current_dir().unwrap();
+    path.push("tests");
+    path.push("workspace");
+
+    let mut root = n3_core::GraphRoot::with_path(path).unwrap();
+
+    let graph = root
+        .find_graph("Classifier", n3_core::UseOrigin::Local)
+        .unwrap();
+    assert_eq!(graph.get_shapes(false).len(), 3);
+
+    // both users publish a model named "MLP"
+    let alice = root
+        .find_graph("MLP", n3_core::UseOrigin::User("alice".to_string()))
+        .unwrap();
+    assert_eq!(alice.get_nodes().len(), 4);
+
+    let bob = root
+        .find_graph("MLP", n3_core::UseOrigin::User("bob".to_string()))
+        .unwrap();
+    assert_eq!(bob.get_nodes().len(), 2);
+
+    // user namespaces are not visible as local models
+    assert!(root.find_graph("MLP", n3_core::UseOrigin::Local).is_err());
+}
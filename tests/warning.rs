@@ -0,0 +1,81 @@
+use n3_core::*;
+
+#[test]
+fn collect_warnings() {
+    static SOUECE: &str = "
+use Conv2d
+use Linear
+use ReLU
+
+[Noisy Model]
+    * W: width = 28
+    * D: depth = 3
+
+    [Conv2d]
+        * kernel size = 5
+        * stride = 1
+        * padding = 2
+
+    extern [ReLU]
+        #0 fixed = N
+        #1 fixed = N
+
+    #0 Input  = 1, W, W
+    #1 Conv2d = 32, W, W
+";
+
+    let mut root = GraphRoot::default();
+
+    let graph = root.compile_from_source(SOUECE).unwrap();
+    let warnings = graph.get_warnings();
+
+    assert_eq!(
+        warnings,
+        [
+            CompileWarning::RedundantVariable {
+                model: "Conv2d".to_string(),
+                variable: "stride".to_string(),
+                value: Value::UInt(1),
+            },
+            CompileWarning::ShadowedModel {
+                model: "ReLU".to_string(),
+            },
+            CompileWarning::UnusedVariable {
+                model: "Noisy Model".to_string(),
+                variable: "depth".to_string(),
+            },
+            CompileWarning::UnusedUse {
+                model: "Linear".to_string(),
+            },
+            CompileWarning::UnusedUse {
+                model: "ReLU".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[2].to_string(),
+        "unused variable \"depth\" (in [Noisy Model])",
+    );
+}
+
+#[test]
+fn no_warnings() {
+    static SOUECE: &str = "
+use Conv2d
+
+[Quiet Model]
+    * W: width = 28
+
+    [Conv2d]
+        * kernel size = 5
+        * padding = 2
+
+    #0 Input  = 1, W, W
+    #1 Conv2d = 32, W, W
+";
+
+    let mut root = GraphRoot::default();
+
+    let graph = root.compile_from_source(SOUECE).unwrap();
+    assert!(graph.get_warnings().is_empty());
+}
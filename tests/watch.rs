@@ -0,0 +1,107 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::fs;
+
+use n3_core::watch::{WatchEvent, Watcher};
+use n3_core::GraphRoot;
+
+use common::Workspace;
+
+static BACKBONE: &str = "
+use Linear
+
+[Backbone]
+
+  #0 Input  = 16
+  #1 Linear = 8
+";
+
+static HEAD: &str = "
+use Backbone
+use Linear
+
+[Head]
+
+  #0 Input    = 16
+  #1 Backbone = 8
+  #2 Linear   = 2
+";
+
+fn summary(events: &[WatchEvent]) -> Vec<String> {
+    events
+        .iter()
+        .map(|event| match event {
+            WatchEvent::Changed { path } => {
+                format!("changed {}", path.file_name().unwrap().to_str().unwrap())
+            }
+            WatchEvent::Compiled { model } => format!("compiled {}", model),
+            WatchEvent::Failed { model, .. } => format!("failed {:?}", model),
+        })
+        .collect()
+}
+
+#[test]
+fn watch_models() {
+    let path = Workspace::new("watch", &[("backbone.n3", BACKBONE), ("head.n3", HEAD)]);
+
+    let mut root = GraphRoot::with_path(&path).unwrap();
+    let mut watcher = Watcher::new(&path, vec!["Head".to_string()]);
+
+    assert_eq!(summary(&watcher.poll(&mut root)), ["compiled Head"]);
+    assert!(watcher.poll(&mut root).is_empty());
+
+    // the head does not fit the wider backbone
+    fs::write(path.join("backbone.n3"), BACKBONE.replace("= 8", "= 32")).unwrap();
+    assert_eq!(
+        summary(&watcher.poll(&mut root)),
+        ["changed backbone.n3", "failed Some(\"Head\")"]
+    );
+    assert!(watcher.poll(&mut root).is_empty());
+
+    fs::write(path.join("head.n3"), HEAD.replace("= 8", "= 32")).unwrap();
+    assert_eq!(
+        summary(&watcher.poll(&mut root)),
+        ["changed head.n3", "compiled Head"]
+    );
+}
+
+#[test]
+fn watch_with_broken_file() {
+    static OTHER: &str = "
+use ReLU
+
+[Other]
+
+  #0 Input = 4
+  #1 ReLU
+";
+
+    let path = Workspace::new(
+        "watch-broken",
+        &[
+            ("backbone.n3", BACKBONE),
+            ("head.n3", HEAD),
+            ("other.n3", OTHER),
+        ],
+    );
+
+    let mut root = GraphRoot::with_path(&path).unwrap();
+    let mut watcher = Watcher::new(&path, vec!["Head".to_string()]);
+    assert_eq!(summary(&watcher.poll(&mut root)), ["compiled Head"]);
+
+    // the head is compiled again, even if the other file breaks in the same poll
+    let backbone = BACKBONE.replace("#1 Linear = 8", "#1 Linear = 8\n  #2 Linear = 8");
+    fs::write(path.join("backbone.n3"), backbone).unwrap();
+    fs::write(path.join("other.n3"), OTHER.replace("[Other]", "[Other")).unwrap();
+    assert_eq!(
+        summary(&watcher.poll(&mut root)),
+        [
+            "changed backbone.n3",
+            "changed other.n3",
+            "failed None",
+            "compiled Head",
+        ]
+    );
+}